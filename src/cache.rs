@@ -0,0 +1,78 @@
+use crate::exercise::ExerciseOutput;
+use crate::metadata::rustc_version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Where cached results are kept, relative to the rustlings directory
+pub const CACHE_DIR: &str = ".rustlings-cache";
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The cached result of compiling and running an exercise
+#[derive(Deserialize, Serialize)]
+pub struct CacheEntry {
+    /// false when compilation itself failed and the exercise never ran
+    pub compiled: bool,
+    pub output: ExerciseOutput,
+}
+
+/// Turn the cache off for this process, e.g. for `--no-cache`
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Hash everything that can change the result of an exercise into a cache key.
+/// The rustc version is always part of the key, so upgrading the toolchain
+/// invalidates every entry.
+pub fn key(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in [rustc_version().as_bytes()].iter().chain(parts) {
+        // Length-prefix each part so that moving bytes between parts changes the key
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn load(key: &str) -> Option<CacheEntry> {
+    if !is_enabled() {
+        return None;
+    }
+    let entry = fs::read_to_string(entry_path(key)).ok()?;
+    serde_json::from_str(&entry).ok()
+}
+
+pub fn store(key: &str, entry: &CacheEntry) {
+    if !is_enabled() {
+        return;
+    }
+    let Ok(serialized) = serde_json::to_string(entry) else {
+        return;
+    };
+    // Write to a temporary file first, so that parallel graders
+    // never read a half-written entry
+    let path = entry_path(key);
+    let temp = path.with_extension(format!("{}.tmp", process::id()));
+    let written = fs::create_dir_all(CACHE_DIR)
+        .and_then(|_| fs::write(&temp, serialized))
+        .and_then(|_| fs::rename(&temp, &path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp); // a missing cache entry only costs a recompile
+    }
+}
+
+fn entry_path(key: &str) -> PathBuf {
+    PathBuf::from(CACHE_DIR).join(format!("{key}.json"))
+}
@@ -0,0 +1,314 @@
+use crate::exercise::Outcome;
+use crate::report::ExerciseCheckList;
+use crate::writers::xml_escape;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many exercises the "hardest exercises" list shows
+pub const HARDEST_COUNT: usize = 10;
+
+/// The class-wide picture, built from one grading report per student
+pub struct ClassReport {
+    /// sorted by name
+    pub students: Vec<StudentTotal>,
+    /// in `info.toml` order
+    pub exercises: Vec<ExerciseSummary>,
+}
+
+pub struct StudentTotal {
+    pub name: String,
+    pub exercises: usize,
+    pub succeeds: usize,
+    pub score: f64,
+    pub max_score: f64,
+}
+
+pub struct ExerciseSummary {
+    pub name: String,
+    pub category: String,
+    position: usize,
+    /// number of students whose report contains the exercise
+    pub attempts: usize,
+    pub passed: usize,
+    /// how many students ended up with each outcome
+    pub outcomes: BTreeMap<Outcome, usize>,
+}
+
+impl ExerciseSummary {
+    pub fn pass_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.passed as f64 / self.attempts as f64
+        }
+    }
+}
+
+impl ClassReport {
+    /// Load every `*.json` report below `dir`. A report without a student name
+    /// is attributed to its file name. Other JSON files, like the summaries
+    /// `submit` writes next to the reports, are skipped with a warning.
+    pub fn load(dir: &Path) -> Result<ClassReport, Box<dyn Error>> {
+        let pattern = dir.join("**").join("*.json");
+        let mut paths: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())?
+            .filter_map(Result::ok)
+            .collect();
+        paths.sort();
+        let mut reports = Vec::new();
+        for path in paths {
+            let report = match ExerciseCheckList::load(&path.to_string_lossy()) {
+                Ok(report) => report,
+                Err(e) => {
+                    println!("Skipping {}, not a grading report: {e}", path.display());
+                    continue;
+                }
+            };
+            let name = report.user_name.clone().unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            reports.push((name, report));
+        }
+        if reports.is_empty() {
+            return Err(format!("No reports found in {}", dir.display()).into());
+        }
+        Ok(ClassReport::new(&reports))
+    }
+
+    pub fn new(reports: &[(String, ExerciseCheckList)]) -> ClassReport {
+        let mut students: Vec<StudentTotal> = reports
+            .iter()
+            .map(|(name, report)| StudentTotal {
+                name: name.clone(),
+                exercises: report.statistics.total_exercations,
+                succeeds: report.statistics.total_succeeds,
+                score: report.statistics.score(),
+                max_score: if report.statistics.max_score == 0.0 {
+                    report.statistics.total_exercations as f64
+                } else {
+                    report.statistics.max_score
+                },
+            })
+            .collect();
+        students.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut exercises: HashMap<&str, ExerciseSummary> = HashMap::new();
+        for (_, report) in reports {
+            for result in &report.exercises {
+                let summary = exercises
+                    .entry(result.name.as_str())
+                    .or_insert_with(|| ExerciseSummary {
+                        name: result.name.clone(),
+                        category: result.category.clone(),
+                        position: result.position,
+                        attempts: 0,
+                        passed: 0,
+                        outcomes: BTreeMap::new(),
+                    });
+                summary.attempts += 1;
+                summary.passed += usize::from(result.result);
+                // Failures from reports that predate outcomes have no kind to count them under
+                if result.outcome != Outcome::Unknown {
+                    *summary.outcomes.entry(result.outcome).or_default() += 1;
+                }
+            }
+        }
+        let mut exercises: Vec<ExerciseSummary> = exercises.into_values().collect();
+        exercises.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.name.cmp(&b.name)));
+
+        ClassReport {
+            students,
+            exercises,
+        }
+    }
+
+    /// The exercises with the lowest pass rates, hardest first
+    pub fn hardest(&self) -> Vec<&ExerciseSummary> {
+        let mut hardest: Vec<&ExerciseSummary> = self.exercises.iter().collect();
+        hardest.sort_by(|a, b| a.pass_rate().total_cmp(&b.pass_rate()));
+        hardest.truncate(HARDEST_COUNT);
+        hardest
+    }
+
+    /// Failing outcomes that occur in any report, in declaration order.
+    /// Passes have a column of their own.
+    fn outcomes(&self) -> Vec<Outcome> {
+        let mut outcomes: Vec<Outcome> = self
+            .exercises
+            .iter()
+            .flat_map(|exercise| exercise.outcomes.keys().copied())
+            .filter(|outcome| *outcome != Outcome::Passed)
+            .collect();
+        outcomes.sort();
+        outcomes.dedup();
+        outcomes
+    }
+
+    /// One row per exercise: pass rate and how many students got each outcome
+    pub fn exercises_csv(&self) -> String {
+        let outcomes = self.outcomes();
+        let mut csv = String::from("exercise,category,attempts,passed,pass_rate");
+        for outcome in &outcomes {
+            csv.push(',');
+            csv.push_str(&csv_field(&outcome.to_string()));
+        }
+        csv.push('\n');
+        for exercise in &self.exercises {
+            let _ = write!(
+                csv,
+                "{},{},{},{},{:.3}",
+                csv_field(&exercise.name),
+                csv_field(&exercise.category),
+                exercise.attempts,
+                exercise.passed,
+                exercise.pass_rate()
+            );
+            for outcome in &outcomes {
+                let _ = write!(csv, ",{}", exercise.outcomes.get(outcome).unwrap_or(&0));
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// One row per student with their totals
+    pub fn students_csv(&self) -> String {
+        let mut csv = String::from("student,exercises,passed,score,max_score\n");
+        for student in &self.students {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{:.2},{:.2}",
+                csv_field(&student.name),
+                student.exercises,
+                student.succeeds,
+                student.score,
+                student.max_score
+            );
+        }
+        csv
+    }
+
+    /// A single static page with every table, styles included
+    pub fn html(&self) -> String {
+        let outcomes = self.outcomes();
+        let average = if self.students.is_empty() {
+            0.0
+        } else {
+            self.students.iter().map(|s| s.score).sum::<f64>() / self.students.len() as f64
+        };
+        let mut html = String::from(HTML_HEAD);
+        let _ = writeln!(
+            html,
+            "<p>{} students, {} exercises, average score {:.2}</p>",
+            self.students.len(),
+            self.exercises.len(),
+            average
+        );
+
+        html.push_str("<h2>Hardest exercises</h2>\n<table>\n");
+        html.push_str("<tr><th>Exercise</th><th>Pass rate</th><th>Most common failure</th></tr>\n");
+        for exercise in self.hardest() {
+            let failure = exercise
+                .outcomes
+                .iter()
+                .filter(|(outcome, _)| **outcome != Outcome::Passed)
+                .max_by_key(|(_, count)| **count)
+                .map(|(outcome, count)| format!("{outcome} ({count})"))
+                .unwrap_or_default();
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td>{}<td>{}</td></tr>",
+                xml_escape(&exercise.name),
+                pass_rate_cell(exercise.pass_rate()),
+                xml_escape(&failure)
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Exercises</h2>\n<table>\n<tr><th>Exercise</th><th>Category</th><th>Passed</th><th>Pass rate</th>");
+        for outcome in &outcomes {
+            let _ = write!(html, "<th>{}</th>", xml_escape(&outcome.to_string()));
+        }
+        html.push_str("</tr>\n");
+        for exercise in &self.exercises {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{} / {}</td>{}",
+                xml_escape(&exercise.name),
+                xml_escape(&exercise.category),
+                exercise.passed,
+                exercise.attempts,
+                pass_rate_cell(exercise.pass_rate())
+            );
+            for outcome in &outcomes {
+                let _ = write!(html, "<td>{}</td>", exercise.outcomes.get(outcome).unwrap_or(&0));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Students</h2>\n<table>\n");
+        html.push_str("<tr><th>Student</th><th>Passed</th><th>Score</th></tr>\n");
+        for student in &self.students {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{} / {}</td><td>{:.2} / {:.2}</td></tr>",
+                xml_escape(&student.name),
+                student.succeeds,
+                student.exercises,
+                student.score,
+                student.max_score
+            );
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    /// Write `exercises.csv`, `students.csv` and `index.html` into `dir`
+    pub fn write(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("exercises.csv"), self.exercises_csv())?;
+        fs::write(dir.join("students.csv"), self.students_csv())?;
+        fs::write(dir.join("index.html"), self.html())?;
+        Ok(())
+    }
+}
+
+// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// A table cell with the pass rate drawn as a bar behind the percentage
+fn pass_rate_cell(rate: f64) -> String {
+    let percent = rate * 100.0;
+    format!(
+        "<td class=\"rate\"><span style=\"width: {percent:.0}%\"></span>{percent:.1} %</td>"
+    )
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Rustlings class report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+th { background: #f0f0f0; }
+td.rate { position: relative; min-width: 8em; }
+td.rate span { position: absolute; left: 0; top: 0; bottom: 0; background: #c8e6c9; z-index: -1; }
+</style>
+</head>
+<body>
+<h1>Rustlings class report</h1>
+"#;
@@ -0,0 +1,41 @@
+use chrono::DateTime;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parse an RFC 3339 deadline such as `2025-11-30T23:59:59+08:00`
+/// into seconds since the Unix epoch
+pub fn parse_deadline(deadline: &str) -> Result<u64, String> {
+    let timestamp = DateTime::parse_from_rfc3339(deadline)
+        .map_err(|e| {
+            format!("invalid deadline `{deadline}` ({e}), expected e.g. 2025-11-30T23:59:59+08:00")
+        })?
+        .timestamp();
+    u64::try_from(timestamp).map_err(|_| format!("deadline `{deadline}` is before 1970"))
+}
+
+/// When the graded version of `path` was submitted, in seconds since the Unix epoch:
+/// the commit time of the last commit that changed it, or now if it has
+/// uncommitted changes or was never committed. `None` outside a git repository.
+/// Commit times are set by whoever commits, so they are only as trustworthy as the student.
+pub fn submitted_at(path: &Path) -> Option<u64> {
+    let status = git(&["status", "--porcelain", "--"], path)?;
+    if !status.is_empty() {
+        return SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|now| now.as_secs());
+    }
+    git(&["log", "-1", "--format=%ct", "--"], path)?
+        .parse()
+        .ok()
+}
+
+// Trimmed stdout of a successful git command about `path`
+fn git(args: &[&str], path: &Path) -> Option<String> {
+    let output = Command::new("git").args(args).arg(path).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,96 @@
+use crate::report::{ExerciseCheckList, ExerciseResult};
+use console::style;
+use std::collections::HashMap;
+
+// Timing changes smaller than this are noise, whatever the slowdown factor
+const MIN_TIMING_REGRESSION_MS: u64 = 100;
+
+/// What changed between two grading runs of the same course
+pub struct ReportDiff<'a> {
+    pub newly_passing: Vec<&'a ExerciseResult>,
+    pub newly_failing: Vec<&'a ExerciseResult>,
+    pub old_score: f64,
+    pub new_score: f64,
+    /// exercises that got slower by more than the slowdown factor, with their old duration
+    pub timing_regressions: Vec<(&'a ExerciseResult, u64)>,
+}
+
+impl<'a> ReportDiff<'a> {
+    /// Compare `new` against `old`. Exercises missing from `old` count as previously failing.
+    pub fn new(
+        old: &'a ExerciseCheckList,
+        new: &'a ExerciseCheckList,
+        slowdown: f64,
+    ) -> ReportDiff<'a> {
+        let old_results: HashMap<&str, &ExerciseResult> =
+            old.exercises.iter().map(|e| (e.name.as_str(), e)).collect();
+
+        let mut diff = ReportDiff {
+            newly_passing: Vec::new(),
+            newly_failing: Vec::new(),
+            old_score: old.statistics.score(),
+            new_score: new.statistics.score(),
+            timing_regressions: Vec::new(),
+        };
+        for result in &new.exercises {
+            let previous = old_results.get(result.name.as_str());
+            let passed_before = previous.is_some_and(|e| e.result);
+            if result.result && !passed_before {
+                diff.newly_passing.push(result);
+            } else if !result.result && passed_before {
+                diff.newly_failing.push(result);
+            }
+            // Reports from before timings were recorded have no duration to compare to
+            if let Some(previous) = previous.filter(|e| e.duration_ms > 0) {
+                let slower = result.duration_ms as f64 > previous.duration_ms as f64 * slowdown;
+                let noticeable =
+                    result.duration_ms >= previous.duration_ms + MIN_TIMING_REGRESSION_MS;
+                if slower && noticeable {
+                    diff.timing_regressions.push((result, previous.duration_ms));
+                }
+            }
+        }
+        diff
+    }
+
+    /// Whether anything got worse: an exercise started failing,
+    /// the score went down, or an exercise got noticeably slower
+    pub fn has_regressions(&self) -> bool {
+        !self.newly_failing.is_empty()
+            || self.new_score < self.old_score
+            || !self.timing_regressions.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!("Newly passing ({}):", self.newly_passing.len());
+        for result in &self.newly_passing {
+            println!("  {} {}", style("+").green(), result.name);
+        }
+        println!("Newly failing ({}):", self.newly_failing.len());
+        for result in &self.newly_failing {
+            println!(
+                "  {} {} ({})",
+                style("-").red(),
+                result.name,
+                result.outcome
+            );
+        }
+        let delta = self.new_score - self.old_score;
+        let delta = if delta < 0.0 {
+            style(format!("{delta:.2}")).red()
+        } else {
+            style(format!("+{delta:.2}")).green()
+        };
+        println!(
+            "Score: {:.2} -> {:.2} ({})",
+            self.old_score, self.new_score, delta
+        );
+        println!("Timing regressions ({}):", self.timing_regressions.len());
+        for (result, old_duration) in &self.timing_regressions {
+            println!(
+                "  {}: {} ms -> {} ms",
+                result.name, old_duration, result.duration_ms
+            );
+        }
+    }
+}
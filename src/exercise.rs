@@ -1,27 +1,83 @@
+use crate::cache::{self, CacheEntry};
+use crate::golden::{self, OutputMatch};
+use crate::hidden::HiddenTests;
+use crate::sign::SIGNING_KEY_ENV;
+use crate::tamper::test_modules_in_place;
+use crate::variant::Param;
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
+use std::ffi::OsStr;
 use std::fmt::{self, Display, Formatter};
-use std::fs::{self, remove_file, File};
-use std::io::Read;
-use std::path::PathBuf;
-use std::process::{self, Command, Output};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, ExitStatus, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 const RUSTC_COLOR_ARGS: &[&str] = &["--color", "always"];
-const RUSTC_EDITION_ARGS: &[&str] = &["--edition", "2021"];
+const DEFAULT_EDITION: &str = "2021";
+const CLIPPY_ARGS: &[&str] = &["--", "-D", "warnings", "-D", "clippy::float_cmp"];
 const I_AM_DONE_REGEX: &str = r"(?m)^\s*//?\s*I\s+AM\s+NOT\s+DONE";
 const CONTEXT: usize = 2;
-const CLIPPY_CARGO_TOML_PATH: &str = "./exercises/clippy/Cargo.toml";
-const BUILD_SCRIPT_CARGO_TOML_PATH: &str = "./exercises/tests/Cargo.toml";
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MEMORY_LIMIT_MB: u64 = 1024;
+const DEFAULT_POINTS: f64 = 1.0;
+const LIMIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const MEMORY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+pub const HIDDEN_TESTS_MODULE: &str = "rustlings_hidden_tests";
 
-// 获取临时文件名（确保唯一性）
+// 本进程所有构建目录的上级目录
 #[inline]
-fn temp_file() -> String {
-    let thread_id: String = format!("{:?}", std::thread::current().id())
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .collect();
-    format!("./temp_{}_{}", process::id(), thread_id)
+fn build_root() -> PathBuf {
+    env::temp_dir().join(format!("rustlings_{}", process::id()))
+}
+
+// 获取练习私有的构建目录（按进程和练习名区分，并行批改时互不干扰）
+#[inline]
+fn build_dir(name: &str) -> PathBuf {
+    build_root().join(name)
+}
+
+// 删除本进程的构建目录，所有练习都编译运行完之后调用一次；
+// 每个练习只删除自己的目录，否则会和正在创建目录的其他练习冲突
+pub fn remove_build_root() {
+    let _ = fs::remove_dir_all(build_root());
+}
+
+// 创建编译或运行练习的命令，去掉报告签名密钥的环境变量，
+// 学生的代码、构建脚本和测试都读不到它
+fn clean_command(program: impl AsRef<OsStr>) -> Command {
+    let mut command = Command::new(program);
+    command.env_remove(SIGNING_KEY_ENV);
+    command
+}
+
+// 生成 Clippy 和构建脚本模式使用的 Cargo.toml
+fn cargo_toml(name: &str, path: &Path, build_script: Option<&Path>, edition: &str) -> String {
+    let build = build_script
+        .map(|script| format!("build = {:?}\n", script.display().to_string()))
+        .unwrap_or_default();
+    format!(
+        r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "{}"
+{}
+[[bin]]
+name = "{}"
+path = {:?}
+
+[workspace]
+"#,
+        name,
+        edition,
+        build,
+        name,
+        path.display().to_string()
+    )
 }
 
 // 练习模式枚举
@@ -32,12 +88,35 @@ pub enum Mode {
     Test,       // 作为测试编译
     Clippy,     // 使用 clippy 检查
     BuildScript,// 使用构建脚本运行
+    Mutation,   // 学生编写的测试需发现 mutants 中的每个错误实现
+    #[serde(rename = "compile_fail")]
+    CompileFail,// 必须被 rustc 拒绝（可指定预期的错误码）
+    Doctest,    // 作为库编译，并用 rustdoc 运行文档示例
 }
 
 // 练习列表结构体
 #[derive(Deserialize)]
 pub struct ExerciseList {
     pub exercises: Vec<Exercise>,
+    #[serde(default)]
+    pub limits: Limits,         // 全局默认的运行限制
+    #[serde(default)]
+    pub deadlines: Deadlines,   // 按分类设置的截止时间
+}
+
+// 运行限制（info.toml 中的 [limits] 表）
+#[derive(Deserialize, Default, Debug)]
+pub struct Limits {
+    pub timeout_secs: Option<u64>,      // 运行超时时间（秒）
+    pub memory_limit_mb: Option<u64>,   // 常驻内存上限（MB）
+}
+
+// 截止时间（info.toml 中的 [deadlines] 表）
+#[derive(Deserialize, Default, Debug)]
+pub struct Deadlines {
+    pub late_penalty: Option<f64>,                  // 迟交扣除的分数比例（0 到 1），默认不扣分
+    #[serde(default)]
+    pub categories: BTreeMap<String, String>,      // 分类名 -> RFC 3339 格式的截止时间
 }
 
 // 练习结构体（从 info.toml 反序列化）
@@ -47,6 +126,53 @@ pub struct Exercise {
     pub path: PathBuf,          // 源代码路径
     pub mode: Mode,             // 练习模式
     pub hint: String,           // 提示信息
+    pub timeout_secs: Option<u64>,      // 覆盖默认的运行超时时间
+    pub memory_limit_mb: Option<u64>,   // 覆盖默认的内存上限
+    pub points: Option<f64>,            // 分值，默认为 1 分
+    pub category: Option<String>,       // 所属分类，默认为所在目录名
+    pub deadline: Option<String>,       // 截止时间，默认使用所属分类的截止时间
+    pub late_penalty: Option<f64>,      // 覆盖默认的迟交扣分比例
+    pub template: Option<PathBuf>,      // 模板路径，按学生的种子生成 path 处的练习
+    #[serde(default)]
+    pub params: BTreeMap<String, Param>,    // 模板中 {{name}} 占位符的取值范围
+    #[serde(default)]
+    pub mutants: Vec<PathBuf>,          // 变异模式下替换练习实现的错误版本
+    #[serde(default)]
+    pub graded_tests: Vec<String>,      // 计入部分得分的测试，如 tests::adds；默认为课程测试模块中的测试
+    #[serde(default)]
+    pub editable_tests: bool,           // 学生要改写测试模块（如 tests2），测试模块不做指纹
+    pub edition: Option<String>,        // Rust edition，默认为 2021
+    #[serde(default)]
+    pub rustc_flags: Vec<String>,       // 额外传给 rustc 的参数，如 -O 或 --cfg
+    #[serde(default)]
+    pub args: Vec<String>,              // 运行练习时传入的命令行参数
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,  // 编译和运行练习时设置的环境变量
+    pub stdin: Option<String>,          // 运行练习时写入标准输入的内容
+    pub expected_output: Option<String>,        // 编译模式的练习应当输出的内容
+    pub expected_output_file: Option<PathBuf>,  // 或从文件中读取应当输出的内容
+    #[serde(default)]
+    pub output_match: OutputMatch,              // 输出的比较方式，默认忽略行尾空白
+    pub expected_error: Option<String>, // compile_fail 模式下预期的错误码，如 E0382
+}
+
+// 练习的最终结果
+#[derive(Deserialize, Serialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,                     // 通过
+    CompileError,               // 编译失败（包括 Clippy 检查不通过）
+    TestFailure,                // 测试未通过
+    RuntimeError,               // 运行时出错（非零退出码或 panic）
+    TimedOut,                   // 运行超时被终止
+    OutOfMemory,                // 内存超限被终止
+    NotDone,                    // 已通过，但仍带有 I AM NOT DONE 标记
+    Tampered,                   // 受保护的测试代码被修改
+    MutantSurvived,             // 学生的测试没能发现某个错误实现
+    WrongOutput,                // 运行成功，但输出与预期不符
+    UnexpectedlyCompiled,       // compile_fail 模式的练习却编译通过了
+    GraderError,                // 无法判定结果（如错误实现无法与测试一起编译），需要人工检查
+    Unknown,                    // 未通过，但旧报告没有记录原因；不计入按结果分类的统计
 }
 
 // 练习状态枚举
@@ -59,6 +185,7 @@ pub enum State {
 // 未完成练习的上下文信息
 #[derive(PartialEq, Debug)]
 pub struct ContextLine {
+    pub path: PathBuf,          // 所在的源文件
     pub line: String,           // 待完成的源代码行
     pub number: usize,          // 行号
     pub important: bool,        // 是否为关键行
@@ -67,239 +194,1089 @@ pub struct ContextLine {
 // 编译后的练习结构体
 pub struct CompiledExercise<'a> {
     exercise: &'a Exercise,
-    _handle: FileHandle,
+    _handle: Option<FileHandle>,    // 命中缓存时没有构建目录
+    cache_key: String,
+    cached: Option<ExerciseOutput>, // 缓存中的运行结果
 }
 
 // 练习输出结果
-#[derive(Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ExerciseOutput {
     pub stdout: String,         // 标准输出内容
     pub stderr: String,         // 标准错误内容
+    pub outcome: Outcome,       // 本次编译或运行的结果
+    #[serde(default)]
+    pub tests: BTreeMap<String, bool>,      // 测试框架记录的每个测试是否通过
+    #[serde(default)]
+    pub mutants: BTreeMap<String, bool>,    // 每个错误实现是否被学生的测试发现
+}
+
+impl ExerciseOutput {
+    fn new(stdout: String, stderr: String, outcome: Outcome) -> ExerciseOutput {
+        ExerciseOutput {
+            stdout,
+            stderr,
+            outcome,
+            tests: BTreeMap::new(),
+            mutants: BTreeMap::new(),
+        }
+    }
+}
+
+// 文件句柄（用于自动清理私有构建目录）
+struct FileHandle {
+    dir: PathBuf,
 }
 
-// 文件句柄（用于自动清理临时文件）
-struct FileHandle;
+impl FileHandle {
+    fn create(dir: PathBuf) -> FileHandle {
+        // 清掉上次中断时可能残留的内容，保证每次都从干净的目录开始构建
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Failed to create exercise build directory");
+        FileHandle { dir }
+    }
+}
 
 impl Drop for FileHandle {
     fn drop(&mut self) {
-        clean();
+        let _ = fs::remove_dir_all(&self.dir); // 忽略删除失败（目录可能已被清理）
     }
 }
 
-// 清理临时文件
-#[inline]
-fn clean() {
-    let temp_path = temp_file();
-    let _ = remove_file(&temp_path); // 忽略删除失败（文件可能已被清理）
+// 在后台线程中读取子进程的输出，避免管道写满导致子进程阻塞
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+// 在后台线程中把 input 写入子进程的标准输入，写完后关闭管道，子进程随后读到 EOF
+fn write_in_background(pipe: Option<impl Write + Send + 'static>, input: Option<String>) {
+    if let (Some(mut pipe), Some(input)) = (pipe, input) {
+        // 子进程可能不读取全部输入就退出，写入失败可以忽略
+        thread::spawn(move || pipe.write_all(input.as_bytes()));
+    }
+}
+
+// 读取子进程所在进程组当前的常驻内存总和（MB），仅在 Linux 上可用。
+// 子进程自成一组，cargo 启动的构建脚本和测试程序也算在内
+#[cfg(target_os = "linux")]
+fn resident_memory_mb(child: &Child) -> Option<u64> {
+    let mut kb = 0;
+    for entry in fs::read_dir("/proc").ok()?.filter_map(Result::ok) {
+        // /proc/<pid>/stat 的第五个字段是进程组，进程名可能含空格，从最后一个 ')' 之后开始数
+        let Ok(stat) = fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        let Some((_, fields)) = stat.rsplit_once(')') else {
+            continue;
+        };
+        if fields.split_whitespace().nth(2) != Some(child.id().to_string().as_str()) {
+            continue;
+        }
+        let status = fs::read_to_string(entry.path().join("status")).unwrap_or_default();
+        kb += status
+            .lines()
+            .find(|line| line.starts_with("VmRSS:"))
+            .and_then(|line| line.split_whitespace().nth(1)?.parse::<u64>().ok())
+            .unwrap_or(0);
+    }
+    Some(kb / 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_memory_mb(_child: &Child) -> Option<u64> {
+    None
+}
+
+// 让子进程自成一个进程组，超出限制时连同它启动的进程一起杀掉
+#[cfg(unix)]
+fn own_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(not(unix))]
+fn own_process_group(_command: &mut Command) {}
+
+// 用 RLIMIT_DATA 限制子进程的数据段（堆和私有的可写映射），由内核强制执行：
+// 超出时分配立即失败，不会在两次检查之间超过上限。cargo 启动的进程继承同样的限制
+#[cfg(unix)]
+fn limit_memory(command: &mut Command, memory_limit_mb: u64) {
+    use std::os::unix::process::CommandExt;
+    let bytes = memory_limit_mb.saturating_mul(1024 * 1024) as libc::rlim_t;
+    // pre_exec 在 fork 之后、exec 之前运行，其中只调用异步信号安全的 setrlimit
+    unsafe {
+        command.pre_exec(move || {
+            let limit = libc::rlimit {
+                rlim_cur: bytes,
+                rlim_max: bytes,
+            };
+            if libc::setrlimit(libc::RLIMIT_DATA, &limit) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn limit_memory(_command: &mut Command, _memory_limit_mb: u64) {}
+
+// 分配因内存上限失败时，Rust 程序打印这条信息后中止
+fn allocation_failed(output: &Output) -> bool {
+    !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("memory allocation of ")
+}
+
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    // 进程组号即子进程的 pid，向负的组号发信号会送达组内的每个进程
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+// 等待子进程结束，超出时间或内存限制时将其杀掉。内存上限主要由 limit_memory
+// 交给内核执行；这里只是偶尔检查整个进程组的常驻内存，作为后备：
+// RLIMIT_DATA 按进程计算，不包括共享映射，也不能在所有平台上设置
+fn wait_with_limits(
+    child: &mut Child,
+    timeout: Duration,
+    memory_limit_mb: u64,
+) -> io::Result<(ExitStatus, Option<Outcome>)> {
+    let start = Instant::now();
+    let mut last_memory_check = start;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, None));
+        }
+        let check_memory = last_memory_check.elapsed() >= MEMORY_POLL_INTERVAL;
+        if check_memory {
+            last_memory_check = Instant::now();
+        }
+        let exceeded = if start.elapsed() >= timeout {
+            Some(Outcome::TimedOut)
+        } else if check_memory && resident_memory_mb(child).is_some_and(|mb| mb > memory_limit_mb) {
+            Some(Outcome::OutOfMemory)
+        } else {
+            None
+        };
+        if exceeded.is_some() {
+            kill_process_group(child);
+            return Ok((child.wait()?, exceeded));
+        }
+        thread::sleep(LIMIT_POLL_INTERVAL);
+    }
+}
+
+// 编译错误实现时使用的源码：学生的测试模块留在原来的行和列上，错误实现接在文件末尾
+// （条目的先后顺序不影响编译），line!() 和 column!() 在真实实现和错误实现中结果相同。
+// 错误实现开头的内部属性（#![...]）只能位于文件开头，移到第一行行首
+fn mutant_source(implementation: &str, tests: &str) -> String {
+    let mut attributes = Vec::new();
+    let mut body = Vec::new();
+    let mut leading = true;
+    for line in implementation.lines() {
+        let trimmed = line.trim();
+        leading = leading && (trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("#!["));
+        if leading && trimmed.starts_with("#![") {
+            attributes.push(trimmed);
+        } else {
+            body.push(line);
+        }
+    }
+    let mut source = attributes.join(" ");
+    if !source.is_empty() {
+        source.push(' ');
+    }
+    source.push_str(tests);
+    source.push('\n');
+    source.push_str(&body.join("\n"));
+    source.push('\n');
+    source
+}
+
+// 提取第一处未完成标记前后的源代码行
+fn pending_context(re: &Regex, path: &Path, source: &str) -> Vec<ContextLine> {
+    let matched_line_index = source
+        .lines()
+        .enumerate()
+        .find(|(_, line)| re.is_match(line))
+        .map(|(i, _)| i)
+        .expect("Matched line should exist");
+
+    let min_line = (matched_line_index as i32 - CONTEXT as i32).max(0) as usize;
+    let max_line = matched_line_index + CONTEXT;
+
+    source
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| *i >= min_line && *i <= max_line)
+        .map(|(i, line)| ContextLine {
+            path: path.to_path_buf(),
+            line: line.to_string(),
+            number: i + 1, // 行号从 1 开始
+            important: i == matched_line_index,
+        })
+        .collect()
+}
+
+impl ExerciseList {
+    // 将 [limits] 和 [deadlines] 中的默认值填入没有单独配置的练习
+    pub fn into_exercises(self) -> Vec<Exercise> {
+        let timeout_secs = self.limits.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let memory_limit_mb = self.limits.memory_limit_mb.unwrap_or(DEFAULT_MEMORY_LIMIT_MB);
+        let deadlines = self.deadlines;
+        self.exercises
+            .into_iter()
+            .map(|mut exercise| {
+                exercise.timeout_secs.get_or_insert(timeout_secs);
+                exercise.memory_limit_mb.get_or_insert(memory_limit_mb);
+                if exercise.deadline.is_none() {
+                    exercise.deadline = deadlines.categories.get(&exercise.category()).cloned();
+                }
+                if let Some(late_penalty) = deadlines.late_penalty {
+                    exercise.late_penalty.get_or_insert(late_penalty);
+                }
+                exercise
+            })
+            .collect()
+    }
 }
 
 impl Exercise {
     // 编译练习
-    pub fn compile(&self) -> Result<CompiledExercise, ExerciseOutput> {
+    pub fn compile(&self) -> Result<CompiledExercise<'_>, ExerciseOutput> {
+        self.compile_with(String::new())
+    }
+
+    // 批改时编译练习：测试模式的练习会拼接上隐藏测试，
+    // 读不到隐藏测试时只有这个练习记为批改出错
+    pub fn compile_for_grading(&self, hidden_tests: &HiddenTests) -> Result<CompiledExercise<'_>, ExerciseOutput> {
+        match self.hidden_test_source(hidden_tests) {
+            Ok(hidden) => self.compile_with(hidden),
+            Err(e) => Err(ExerciseOutput::new(String::new(), e, Outcome::GraderError)),
+        }
+    }
+
+    // 把每个隐藏测试文件包装成一个测试模块，追加在练习源码之后，
+    // 这样学生代码的行号不变；练习中的条目通过 use super::* 可见
+    pub fn hidden_test_source(&self, hidden_tests: &HiddenTests) -> Result<String, String> {
+        let files = hidden_tests.files(&self.name);
+        if files.is_empty() {
+            return Ok(String::new());
+        }
+        if !matches!(self.mode, Mode::Test) {
+            return Err(format!("Hidden tests only apply to exercises in test mode, {} isn't one", self.name));
+        }
+        let mut source = String::new();
+        for (index, file) in files.iter().enumerate() {
+            let tests = hidden_tests
+                .read(file)
+                .map_err(|e| format!("Failed to read hidden tests {file}: {e}"))?;
+            source.push_str(&format!(
+                "\n#[cfg(test)]\nmod {HIDDEN_TESTS_MODULE}_{index} {{\n#[allow(unused_imports)]\nuse super::*;\n{tests}\n}}\n"
+            ));
+        }
+        Ok(source)
+    }
+
+    // hidden 为要拼接进练习的隐藏测试源码，为空时直接编译练习文件
+    fn compile_with(&self, hidden: String) -> Result<CompiledExercise<'_>, ExerciseOutput> {
+        // 源码、模式、工具链和参数都没变时直接使用上次的结果
+        let cache_key = self.cache_key(&hidden);
+        if let Some(entry) = cache::load(&cache_key) {
+            return if entry.compiled {
+                Ok(CompiledExercise {
+                    exercise: self,
+                    _handle: None,
+                    cache_key,
+                    cached: Some(entry.output),
+                })
+            } else {
+                Err(entry.output)
+            };
+        }
+
+        let handle = FileHandle::create(build_dir(&self.name));
+        let binary = self.binary_path();
+        // 带隐藏测试时编译私有构建目录中拼接后的副本，练习文件本身保持不变
+        let source = if hidden.is_empty() {
+            self.root_file()
+        } else {
+            let spliced = if self.is_crate() {
+                // 目录形式的练习整个复制过去，根文件中的 mod 声明才能找到其余文件
+                let copy = handle.dir.join("src");
+                for file in self.files() {
+                    let target = copy.join(file.strip_prefix(&self.path).unwrap_or(&file));
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent).expect("Failed to copy exercise for hidden tests");
+                    }
+                    fs::copy(&file, &target).expect("Failed to copy exercise for hidden tests");
+                }
+                copy.join(self.root_file().strip_prefix(&self.path).unwrap_or(Path::new("main.rs")))
+            } else {
+                handle.dir.join(format!("{}.rs", self.name))
+            };
+            let original = fs::read_to_string(self.root_file()).unwrap_or_default();
+            fs::write(&spliced, original + &hidden).expect("Failed to write exercise with hidden tests");
+            spliced
+        };
+        let mut tests = BTreeMap::new();
+        let mut exceeded = None;
         let cmd_output = match self.mode {
-            Mode::Compile => Command::new("rustc")
-                .arg(self.path.to_str().unwrap())
+            Mode::Compile | Mode::CompileFail => clean_command("rustc")
+                .arg(self.root_file())
                 .arg("-o")
-                .arg(temp_file())
-                .args(RUSTC_COLOR_ARGS)
-                .args(RUSTC_EDITION_ARGS)
+                .arg(&binary)
+                .args(self.rustc_args())
+                .envs(&self.env)
                 .output()
                 .expect("Failed to run rustc command"),
 
-            Mode::Test => Command::new("rustc")
+            Mode::Test | Mode::Mutation => clean_command("rustc")
                 .arg("--test")
-                .arg(self.path.to_str().unwrap())
+                .arg(source.to_str().unwrap())
                 .arg("-o")
-                .arg(temp_file())
-                .args(RUSTC_COLOR_ARGS)
-                .args(RUSTC_EDITION_ARGS)
+                .arg(&binary)
+                .args(self.rustc_args())
+                .envs(&self.env)
                 .output()
                 .expect("Failed to run rustc test command"),
 
-            Mode::Clippy => {
-                // 生成 Clippy 所需的 Cargo.toml
-                let cargo_toml = format!(
-                    r#"[package]
-name = "{}"
-version = "0.0.1"
-edition = "2021"
-
-[[bin]]
-name = "{}"
-path = "{}.rs"
-"#,
-                    self.name, self.name, self.name
-                );
+            // 先作为库编译，文档示例再链接这个库；库本身的编译错误也就不会被当成示例的失败
+            Mode::Doctest => clean_command("rustc")
+                .arg("--crate-type")
+                .arg("lib")
+                .arg("--crate-name")
+                .arg(self.crate_name())
+                .arg(self.root_file())
+                .arg("-o")
+                .arg(self.rlib_path())
+                .args(self.rustc_args())
+                .envs(&self.env)
+                .output()
+                .expect("Failed to run rustc command"),
 
+            Mode::Clippy => {
                 let cargo_toml_error_msg = if env::var("NO_EMOJI").is_ok() {
                     "Failed to write Clippy Cargo.toml file."
                 } else {
                     "Failed to write 📎 Clippy 📎 Cargo.toml file."
                 };
 
-                // 写入 Cargo.toml
-                fs::write(CLIPPY_CARGO_TOML_PATH, cargo_toml)
+                // 在私有构建目录中写入 Clippy 所需的 Cargo.toml
+                let manifest = handle.dir.join("Cargo.toml");
+                fs::write(&manifest, cargo_toml(&self.name, &self.source_path(), None, self.edition()))
                     .expect(cargo_toml_error_msg);
 
                 // 预编译（确保可执行文件存在）
-                let _ = Command::new("rustc")
-                    .arg(self.path.to_str().unwrap())
+                let _ = clean_command("rustc")
+                    .arg(self.root_file())
                     .arg("-o")
-                    .arg(temp_file())
-                    .args(RUSTC_COLOR_ARGS)
-                    .args(RUSTC_EDITION_ARGS)
+                    .arg(&binary)
+                    .args(self.rustc_args())
+                    .envs(&self.env)
                     .output()
                     .expect("Failed to compile for Clippy");
 
-                // 清理缓存（解决 Clippy 历史问题）
-                Command::new("cargo")
-                    .arg("clean")
-                    .arg("--manifest-path")
-                    .arg(CLIPPY_CARGO_TOML_PATH)
-                    .args(RUSTC_COLOR_ARGS)
-                    .output()
-                    .expect("Failed to run 'cargo clean'");
-
-                // 运行 Clippy 检查
-                Command::new("cargo")
+                // 运行 Clippy 检查（构建目录每次都是全新的，无需 cargo clean）
+                clean_command("cargo")
                     .arg("clippy")
                     .arg("--manifest-path")
-                    .arg(CLIPPY_CARGO_TOML_PATH)
+                    .arg(&manifest)
                     .args(RUSTC_COLOR_ARGS)
-                    .args(&["--", "-D", "warnings", "-D", "clippy::float_cmp"])
+                    .args(CLIPPY_ARGS)
+                    .envs(self.cargo_env())
                     .output()
                     .expect("Failed to run clippy")
             }
 
             Mode::BuildScript => {
-                // 生成构建脚本的 Cargo.toml
-                let cargo_toml = format!(
-                    r#"[package]
-name = "{}"
-version = "0.0.1"
-edition = "2021"
-
-[[bin]]
-name = "{}"
-path = "{}.rs"
-"#,
-                    self.name, self.name, self.name
-                );
-
                 let cargo_toml_error_msg = if env::var("NO_EMOJI").is_ok() {
                     "Failed to write build script Cargo.toml file."
                 } else {
                     "Failed to write 🔨 Build Script 🔨 Cargo.toml file."
                 };
 
-                // 写入 Cargo.toml
-                fs::write(BUILD_SCRIPT_CARGO_TOML_PATH, cargo_toml)
+                // 在私有构建目录中写入构建脚本的 Cargo.toml，build.rs 与练习位于同一目录
+                let source = self.source_path();
+                let build_script = source.with_file_name("build.rs");
+                let manifest = handle.dir.join("Cargo.toml");
+                fs::write(&manifest, cargo_toml(&self.name, &source, Some(&build_script), self.edition()))
                     .expect(cargo_toml_error_msg);
 
-                // 运行测试
-                Command::new("cargo")
-                    .arg("test")
-                    .arg("--manifest-path")
-                    .arg(BUILD_SCRIPT_CARGO_TOML_PATH)
-                    .output()
-                    .expect("Failed to run 'cargo test' for build script")
+                // 运行测试，参数、环境变量和标准输入都交给 cargo test
+                let cargo_test = |test_args: &[&str]| {
+                    let mut command = clean_command("cargo");
+                    command
+                        .arg("test")
+                        .arg("--manifest-path")
+                        .arg(&manifest)
+                        .envs(self.cargo_env())
+                        .arg("--")
+                        .args(test_args);
+                    command
+                };
+                let mut command = cargo_test(&[]);
+                command.args(&self.args);
+                // 构建脚本和测试都在 cargo test 中运行，同样受时间和内存限制
+                let (output, limit) = self.output_limited(command);
+                exceeded = limit;
+                // 测试失败时才需要逐个统计；编译失败时列不出任何测试
+                if !output.status.success() && exceeded.is_none() {
+                    tests = self.test_results(&cargo_test, Outcome::TestFailure);
+                }
+                output
             }
         };
 
-        // 根据编译结果返回对应值
-        if cmd_output.status.success() {
+        // 根据编译结果返回对应值，失败时附带一段说明
+        let stdout = String::from_utf8_lossy(&cmd_output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&cmd_output.stderr).to_string();
+        let failure = match self.mode {
+            _ if exceeded.is_some() => exceeded.map(|outcome| {
+                (outcome, self.limit_message(outcome).unwrap_or_default() + "\n")
+            }),
+            // compile_fail 模式恰好相反：被 rustc 以预期的错误拒绝才算通过
+            Mode::CompileFail => self.check_compile_error(cmd_output.status.success(), &stderr),
+            _ if cmd_output.status.success() => None,
+            // 构建脚本模式下 cargo test 同时负责编译和测试，测试运行过就说明编译通过了
+            Mode::BuildScript if !tests.is_empty() => Some((Outcome::TestFailure, String::new())),
+            _ => Some((Outcome::CompileError, String::new())),
+        };
+        if let Some((outcome, explanation)) = failure {
+            let output = ExerciseOutput {
+                tests,
+                ..ExerciseOutput::new(stdout, explanation + &stderr, outcome)
+            };
+            cache::store(
+                &cache_key,
+                &CacheEntry {
+                    compiled: false,
+                    output: output.clone(),
+                },
+            );
+            Err(output)
+        } else {
             Ok(CompiledExercise {
                 exercise: self,
-                _handle: FileHandle,
+                _handle: Some(handle),
+                cache_key,
+                cached: None,
             })
+        }
+    }
+
+    // compile_fail 模式：代码编译通过，或者 rustc 报出的错误码中没有预期的那个时，
+    // 返回失败的结果和说明；未指定 expected_error 时无从判断失败的原因是否正确，算作批改出错
+    fn check_compile_error(&self, compiled: bool, stderr: &str) -> Option<(Outcome, String)> {
+        let Some(code) = &self.expected_error else {
+            return Some((
+                Outcome::GraderError,
+                format!("{self} is in compile_fail mode but sets no expected_error, so there's no telling whether it fails for the right reason\n"),
+            ));
+        };
+        let expected = format!(" with error {code}");
+        if compiled {
+            return Some((
+                Outcome::UnexpectedlyCompiled,
+                format!("{self} compiled, but rustc should reject it{expected}\n"),
+            ));
+        }
+        let re = Regex::new(r"error\[(E\d+)\]").unwrap();
+        let stderr = console::strip_ansi_codes(stderr);
+        let mut codes: Vec<&str> = re
+            .captures_iter(&stderr)
+            .filter_map(|caps| caps.get(1))
+            .map(|code| code.as_str())
+            .collect();
+        if codes.contains(&code.as_str()) {
+            return None;
+        }
+        codes.sort_unstable();
+        codes.dedup();
+        let found = if codes.is_empty() {
+            "without an error code".to_string()
         } else {
-            Err(ExerciseOutput {
-                stdout: String::from_utf8_lossy(&cmd_output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&cmd_output.stderr).to_string(),
+            format!("with {}", codes.join(", "))
+        };
+        Some((
+            Outcome::CompileError,
+            format!("{self} should fail to compile{expected}, but rustc failed {found}\n"),
+        ))
+    }
+
+    // 缓存键：影响编译和运行结果的所有输入
+    fn cache_key(&self, hidden: &str) -> String {
+        // 目录形式的练习包含所有源文件，文件名也参与计算，以便区分文件的增删和改名
+        let mut source = Vec::new();
+        for path in self.files() {
+            source.extend_from_slice(path.to_string_lossy().as_bytes());
+            source.push(0);
+            source.extend(fs::read(&path).unwrap_or_default());
+            source.push(0);
+        }
+        let build_script = match self.mode {
+            Mode::BuildScript => fs::read(self.root_file().with_file_name("build.rs")).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let mutants: Vec<Vec<u8>> = match self.mode {
+            Mode::Mutation => self
+                .mutants
+                .iter()
+                .map(|path| fs::read(path).unwrap_or_default())
+                .collect(),
+            _ => Vec::new(),
+        };
+        let settings = format!(
+            "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+            self.mode,
+            self.rustc_args(),
+            CLIPPY_ARGS,
+            self.timeout_secs,
+            self.memory_limit_mb,
+            self.args,
+            self.env,
+            self.stdin,
+            self.expected_output().and_then(Result::ok),
+            self.output_match,
+            self.expected_error
+        );
+        let mut parts: Vec<&[u8]> = vec![&source, &build_script, hidden.as_bytes(), settings.as_bytes()];
+        parts.extend(mutants.iter().map(Vec::as_slice));
+        cache::key(&parts)
+    }
+
+    // 作为库编译时的 crate 名称，文档示例通过它引用练习中的条目
+    fn crate_name(&self) -> String {
+        self.name.replace('-', "_")
+    }
+
+    // 练习使用的 Rust edition
+    fn edition(&self) -> &str {
+        self.edition.as_deref().unwrap_or(DEFAULT_EDITION)
+    }
+
+    // 传给 rustc 的参数：颜色（rustc_flags 自己指定了 --color 时不再添加）、edition 和 rustc_flags
+    fn rustc_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.rustc_flags.iter().any(|flag| flag.starts_with("--color")) {
+            args.extend(RUSTC_COLOR_ARGS.iter().map(|arg| arg.to_string()));
+        }
+        args.push("--edition".to_string());
+        args.push(self.edition().to_string());
+        args.extend(self.rustc_flags.iter().cloned());
+        args
+    }
+
+    // 传给 rustdoc 的参数：rustdoc 也接受的 rustc 参数原样传入，-O 和 -g 换成等价的 -C 参数，
+    // 其余参数 rustdoc 不认识，返回说明哪个参数无法转换的错误
+    pub fn rustdoc_args(&self) -> Result<Vec<String>, String> {
+        const WITH_VALUE: [&str; 17] = [
+            "--cfg", "--check-cfg", "-C", "--codegen", "--cap-lints", "-A", "--allow", "-W", "--warn",
+            "-D", "--deny", "-F", "--forbid", "--color", "--edition", "-L", "--extern",
+        ];
+        // 参数和值写在一起的形式，如 -Copt-level=3 或 --cfg=name
+        let joined = |flag: &str| {
+            WITH_VALUE.iter().any(|name| match flag.strip_prefix(name) {
+                Some(value) if name.starts_with("--") => value.starts_with('='),
+                Some(value) => !value.is_empty(),
+                None => false,
             })
+        };
+        let mut args = Vec::new();
+        let mut flags = self.rustc_args().into_iter();
+        while let Some(flag) = flags.next() {
+            match flag.as_str() {
+                "-O" => args.extend(["-C".to_string(), "opt-level=2".to_string()]),
+                "-g" => args.extend(["-C".to_string(), "debuginfo=2".to_string()]),
+                name if WITH_VALUE.contains(&name) => {
+                    args.push(flag.clone());
+                    args.extend(flags.next());
+                }
+                name if joined(name) => args.push(flag.clone()),
+                _ => return Err(format!("rustc flag `{flag}` has no rustdoc equivalent")),
+            }
+        }
+        Ok(args)
+    }
+
+    // 通过 cargo 构建时的环境变量：rustc_flags 经 RUSTFLAGS 传入，edition 已写在 Cargo.toml 中
+    fn cargo_env(&self) -> BTreeMap<String, String> {
+        let mut env = self.env.clone();
+        if !self.rustc_flags.is_empty() {
+            env.insert("RUSTFLAGS".to_string(), self.rustc_flags.join(" "));
         }
+        env
+    }
+
+    // doctest 模式下作为库编译的产物路径
+    fn rlib_path(&self) -> PathBuf {
+        build_dir(&self.name).join(format!("lib{}.rlib", self.crate_name()))
+    }
+
+    // 编译产物在私有构建目录中的路径
+    fn binary_path(&self) -> PathBuf {
+        build_dir(&self.name).join(&self.name)
+    }
+
+    // 练习源文件的绝对路径（私有构建目录中的 Cargo.toml 需要引用它）
+    fn source_path(&self) -> PathBuf {
+        env::current_dir()
+            .expect("Failed to read current directory")
+            .join(self.root_file())
+    }
+
+    // path 是否为目录形式的练习（包含 main.rs 或 lib.rs 以及子模块文件）
+    pub fn is_crate(&self) -> bool {
+        self.path.is_dir()
+    }
+
+    // 交给 rustc 编译的根文件：单文件练习即其本身，目录形式的练习优先使用 main.rs，其次 lib.rs
+    pub fn root_file(&self) -> PathBuf {
+        self.root_file_in(Path::new(""))
+    }
+
+    // 练习位于 dir 下时的根文件，路径相对于 dir
+    fn root_file_in(&self, dir: &Path) -> PathBuf {
+        if !dir.join(&self.path).is_dir() {
+            return self.path.clone();
+        }
+        let main = self.path.join("main.rs");
+        if dir.join(&main).exists() {
+            main
+        } else {
+            self.path.join("lib.rs")
+        }
+    }
+
+    // 属于该练习的所有源文件，根文件在前，其余按路径排序
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files_in(Path::new(""))
+    }
+
+    // 练习位于 dir 下（例如另一份提交中）时属于它的源文件，路径相对于 dir；
+    // 目录形式的练习在各份提交中的文件可能不同
+    pub fn files_in(&self, dir: &Path) -> Vec<PathBuf> {
+        if !dir.join(&self.path).is_dir() {
+            return vec![self.path.clone()];
+        }
+        let root = self.root_file_in(dir);
+        let pattern = dir.join(&self.path).join("**").join("*.rs");
+        let mut files: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+            .map(|paths| {
+                paths
+                    .filter_map(Result::ok)
+                    .filter_map(|path| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+                    .filter(|path| *path != root)
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files.insert(0, root);
+        files
+    }
+
+    // 练习所有源文件的内容，依次拼接
+    pub fn source(&self) -> String {
+        self.files()
+            .iter()
+            .map(|path| fs::read_to_string(path).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // 文件是否属于该练习（file 可以是绝对路径）
+    pub fn owns(&self, file: &Path) -> bool {
+        self.files().iter().any(|own| file.ends_with(own))
     }
 
     // 运行编译后的练习
     pub fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        let arg = match self.mode {
-            Mode::Test => "--show-output",
-            Mode::BuildScript => {
-                return Ok(ExerciseOutput {
-                    stdout: String::new(),
-                    stderr: String::new(),
-                })
+        let mut output = match self.mode {
+            Mode::BuildScript | Mode::CompileFail => {
+                return Ok(ExerciseOutput::new(String::new(), String::new(), Outcome::Passed))
             }
-            _ => "",
-        };
-
-        let cmd_output = Command::new(temp_file())
-            .arg(arg)
-            .output()
-            .expect("Failed to run compiled exercise");
-
-        let output = ExerciseOutput {
-            stdout: String::from_utf8_lossy(&cmd_output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&cmd_output.stderr).to_string(),
+            Mode::Doctest => self.run_doctests(),
+            Mode::Test | Mode::Mutation => self.run_tests(),
+            _ => self.run_binary(&self.binary_path(), &[]),
         };
+        // 学生的测试在正确实现上通过后，再逐个检验错误实现
+        if matches!(self.mode, Mode::Mutation) && output.outcome == Outcome::Passed {
+            self.run_mutants(&mut output);
+        }
+        // 编译模式的练习还要检查输出是否符合预期
+        if matches!(self.mode, Mode::Compile) && output.outcome == Outcome::Passed {
+            self.check_output(&mut output);
+        }
 
-        if cmd_output.status.success() {
+        if output.outcome == Outcome::Passed {
             Ok(output)
         } else {
             Err(output)
         }
     }
 
-    // 获取练习状态（已完成/未完成）
-    pub fn state(&self) -> State {
-        // 读取练习源代码
-        let mut source_file = File::open(&self.path)
-            .expect("Failed to open exercise file");
-        let mut source = String::new();
-        source_file.read_to_string(&mut source)
-            .expect("Failed to read exercise file");
+    // 在运行限制下执行编译好的程序
+    fn run_binary(&self, binary: &Path, harness_args: &[&str]) -> ExerciseOutput {
+        let mut command = clean_command(binary);
+        command.args(harness_args).args(&self.args);
+        self.run_limited(command)
+    }
 
-        // 检查是否包含 "I AM NOT DONE" 标记
-        let re = Regex::new(I_AM_DONE_REGEX).unwrap();
-        if !re.is_match(&source) {
-            return State::Done;
+    // 运行编译好的测试，显示通过的测试的输出，并记下每个测试的结果
+    fn run_tests(&self) -> ExerciseOutput {
+        let binary = self.binary_path();
+        let mut output = self.run_binary(&binary, &["--show-output"]);
+        let harness = |args: &[&str]| {
+            let mut command = clean_command(&binary);
+            command.args(args);
+            command
+        };
+        output.tests = self.test_results(&harness, output.outcome);
+        output
+    }
+
+    // 得出每个测试是否通过：测试由测试框架的 --list 列出（被忽略的不计入），整体运行
+    // 失败时再逐个单独运行，以退出码为准，练习自己打印的内容不会影响结果。
+    // harness 根据测试框架的参数生成命令，outcome 为整体运行的结果
+    fn test_results(
+        &self,
+        harness: &dyn Fn(&[&str]) -> Command,
+        outcome: Outcome,
+    ) -> BTreeMap<String, bool> {
+        if !matches!(outcome, Outcome::Passed | Outcome::TestFailure) {
+            return BTreeMap::new();
         }
+        let list = |extra: &[&str]| -> Vec<String> {
+            let args: Vec<&str> = ["--list"]
+                .iter()
+                .chain(extra)
+                .copied()
+                .chain(self.args.iter().map(String::as_str))
+                .collect();
+            let (output, _) = self.output_limited(harness(&args));
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.strip_suffix(": test"))
+                .map(str::to_string)
+                .collect()
+        };
+        let ignored = list(&["--ignored"]);
+        list(&[])
+            .into_iter()
+            .filter(|name| !ignored.contains(name))
+            .map(|name| {
+                let passed = outcome == Outcome::Passed
+                    || self.run_limited(harness(&["--exact", &name, "--quiet"])).outcome == Outcome::Passed;
+                (name, passed)
+            })
+            .collect()
+    }
 
-        // 提取未完成行的上下文
-        let matched_line_index = source
-            .lines()
-            .enumerate()
-            .find(|(_, line)| re.is_match(line))
-            .map(|(i, _)| i)
-            .expect("Matched line should exist");
+    // 用 rustdoc 编译并运行文档示例，和测试模式一样显示通过的示例的输出；
+    // 练习的 args 作为测试参数（例如过滤条件）传给测试框架
+    fn run_doctests(&self) -> ExerciseOutput {
+        let rustdoc_args = match self.rustdoc_args() {
+            Ok(args) => args,
+            Err(e) => return ExerciseOutput::new(String::new(), format!("{self}: {e}\n"), Outcome::GraderError),
+        };
+        let rustdoc = |test_args: &[&str]| {
+            let mut command = clean_command("rustdoc");
+            command
+                .arg("--test")
+                .arg(self.root_file())
+                .arg("--crate-name")
+                .arg(self.crate_name())
+                .arg("-L")
+                .arg(build_dir(&self.name))
+                .arg("--extern")
+                .arg(format!("{}={}", self.crate_name(), self.rlib_path().display()))
+                .args(&rustdoc_args);
+            for arg in test_args {
+                command.arg("--test-args").arg(arg);
+            }
+            command
+        };
+        let args: Vec<&str> = std::iter::once("--show-output")
+            .chain(self.args.iter().map(String::as_str))
+            .collect();
+        let mut output = self.run_limited(rustdoc(&args));
+        output.tests = self.test_results(&rustdoc, output.outcome);
+        output
+    }
+
+    // 在运行限制下执行命令，练习的环境变量和标准输入也会传给它；
+    // 返回命令的输出，以及超出的限制
+    fn output_limited(&self, mut command: Command) -> (Output, Option<Outcome>) {
+        let timeout = Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        let memory_limit_mb = self.memory_limit_mb.unwrap_or(DEFAULT_MEMORY_LIMIT_MB);
+        own_process_group(&mut command);
+        limit_memory(&mut command, memory_limit_mb);
+        let mut child = command
+            .envs(&self.env)
+            .stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to run compiled exercise");
+        write_in_background(child.stdin.take(), self.stdin.clone());
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
 
-        let min_line = (matched_line_index as i32 - CONTEXT as i32).max(0) as usize;
-        let max_line = matched_line_index + CONTEXT;
+        // 限制运行时间和内存，防止死循环或失控的递归卡住整个批改流程
+        let (status, exceeded) = wait_with_limits(&mut child, timeout, memory_limit_mb)
+            .expect("Failed to wait for compiled exercise");
+        let output = Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        };
+        let exceeded = exceeded.or_else(|| allocation_failed(&output).then_some(Outcome::OutOfMemory));
+        (output, exceeded)
+    }
 
-        let context = source
-            .lines()
-            .enumerate()
-            .filter(|(i, _)| *i >= min_line && *i <= max_line)
-            .map(|(i, line)| ContextLine {
-                line: line.to_string(),
-                number: i + 1, // 行号从 1 开始
-                important: i == matched_line_index,
+    // 在运行限制下执行命令，并根据退出状态判定结果
+    fn run_limited(&self, command: Command) -> ExerciseOutput {
+        let (output, exceeded) = self.output_limited(command);
+        let status = output.status;
+        let outcome = match exceeded {
+            Some(outcome) => outcome,
+            None if status.success() => Outcome::Passed,
+            // rustdoc 以 101 报告失败的文档示例，以 1 退出说明它根本没能运行，问题出在批改配置上
+            None if matches!(self.mode, Mode::Doctest) && status.code() == Some(1) => Outcome::GraderError,
+            None if matches!(self.mode, Mode::Test | Mode::Mutation | Mode::Doctest) => Outcome::TestFailure,
+            None => Outcome::RuntimeError,
+        };
+        ExerciseOutput::new(
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+            outcome,
+        )
+    }
+
+    // 练习应当输出的内容，未配置时为 None
+    pub fn expected_output(&self) -> Option<io::Result<String>> {
+        if let Some(expected) = &self.expected_output {
+            return Some(Ok(expected.clone()));
+        }
+        self.expected_output_file.as_ref().map(|path| {
+            fs::read_to_string(path).map_err(|e| {
+                io::Error::new(e.kind(), format!("Failed to read expected output {}: {e}", path.display()))
             })
-            .collect();
+        })
+    }
+
+    // 输出与预期不符时把结果改为 WrongOutput，并把差异追加到标准错误中
+    fn check_output(&self, output: &mut ExerciseOutput) {
+        let mismatch = match self.expected_output() {
+            None => return,
+            Some(Ok(expected)) => match golden::check(&expected, &output.stdout, self.output_match) {
+                Ok(()) => return,
+                Err(diff) => format!("Output doesn't match the expected output:\n{diff}"),
+            },
+            Some(Err(e)) => e.to_string(),
+        };
+        output.stderr.push_str(&mismatch);
+        output.outcome = Outcome::WrongOutput;
+    }
 
-        State::Pending(context)
+    // 把学生的测试模块和每个错误实现一起编译运行：测试不再全部通过即为发现了该错误。
+    // 每个错误实现的结果记在 mutants 中，并以 `mutant <路径> ... caught|survived`
+    // 的形式追加到标准错误中，供人阅读；无法判定的错误实现不计入，结果改为 GraderError
+    fn run_mutants(&self, output: &mut ExerciseOutput) {
+        let source = fs::read_to_string(self.root_file()).unwrap_or_default();
+        let tests = test_modules_in_place(&source);
+        for (index, mutant) in self.mutants.iter().enumerate() {
+            let caught = match fs::read_to_string(mutant) {
+                Ok(implementation) => self.test_mutant(index, &implementation, &tests, output),
+                Err(e) => {
+                    output
+                        .stderr
+                        .push_str(&format!("Failed to read mutant {}: {e}\n", mutant.display()));
+                    None
+                }
+            };
+            let verdict = match caught {
+                Some(true) => "caught",
+                Some(false) => "survived",
+                None => "not graded",
+            };
+            output
+                .stderr
+                .push_str(&format!("mutant {} ... {verdict}\n", mutant.display()));
+            match caught {
+                Some(caught) => {
+                    output.mutants.insert(mutant.display().to_string(), caught);
+                    if !caught && output.outcome != Outcome::GraderError {
+                        output.outcome = Outcome::MutantSurvived;
+                    }
+                }
+                None => output.outcome = Outcome::GraderError,
+            }
+        }
+    }
+
+    // 编译并运行第 index 个错误实现，返回学生的测试是否发现了它；
+    // 错误实现无法与测试一起编译时无从判定，返回 None。
+    // 错误实现与正确实现的构建方式完全相同（源文件路径、程序路径和测试参数都一样），
+    // 学生的测试无法通过 file!()、argv[0] 等分辨自己测的是哪一个
+    fn test_mutant(
+        &self,
+        index: usize,
+        implementation: &str,
+        tests: &str,
+        output: &mut ExerciseOutput,
+    ) -> Option<bool> {
+        // 源码放在构建目录之外，按练习原本的相对路径存放，编译时去掉前缀
+        let handle = FileHandle::create(build_dir(&format!("{}.mutant", self.name)));
+        for file in self.files() {
+            let target = handle.dir.join(&file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).expect("Failed to write mutant");
+            }
+            fs::copy(&file, &target).expect("Failed to write mutant");
+        }
+        let source = handle.dir.join(self.root_file());
+        fs::write(&source, mutant_source(implementation, tests)).expect("Failed to write mutant");
+        // 正确实现已经运行完毕，直接覆盖它的程序
+        let binary = self.binary_path();
+        let compiled = clean_command("rustc")
+            .arg("--test")
+            .arg(&source)
+            .arg("--remap-path-prefix")
+            .arg(format!("{}=", handle.dir.join("").display()))
+            .arg("-o")
+            .arg(&binary)
+            .args(self.rustc_args())
+            .envs(&self.env)
+            .output()
+            .expect("Failed to run rustc test command");
+        if !compiled.status.success() {
+            output.stderr.push_str(&format!(
+                "Mutant {} doesn't compile together with the tests, so they can't be checked against it:\n",
+                self.mutants[index].display()
+            ));
+            output.stderr.push_str(&String::from_utf8_lossy(&compiled.stderr));
+            return None;
+        }
+        Some(self.run_binary(&binary, &["--show-output"]).outcome != Outcome::Passed)
+    }
+
+    // 描述因超出限制而失败的原因
+    pub fn limit_message(&self, outcome: Outcome) -> Option<String> {
+        match outcome {
+            Outcome::TimedOut => Some(format!(
+                "{} timed out after {} s",
+                self,
+                self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)
+            )),
+            Outcome::OutOfMemory => Some(format!(
+                "{} ran out of memory (limit {} MB)",
+                self,
+                self.memory_limit_mb.unwrap_or(DEFAULT_MEMORY_LIMIT_MB)
+            )),
+            _ => None,
+        }
+    }
+
+    // 获取练习状态（已完成/未完成）
+    pub fn state(&self) -> State {
+        // 依次检查练习的每个源文件，第一个带有 "I AM NOT DONE" 标记的文件决定状态
+        let re = Regex::new(I_AM_DONE_REGEX).unwrap();
+        for path in self.files() {
+            // 读取练习源代码
+            let mut source_file = File::open(&path)
+                .expect("Failed to open exercise file");
+            let mut source = String::new();
+            source_file.read_to_string(&mut source)
+                .expect("Failed to read exercise file");
+
+            if re.is_match(&source) {
+                return State::Pending(pending_context(&re, &path, &source));
+            }
+        }
+        State::Done
     }
 
     // 检查练习是否看起来已完成
     pub fn looks_done(&self) -> bool {
         self.state() == State::Done
     }
+
+    // 练习的分值
+    pub fn points(&self) -> f64 {
+        self.points.unwrap_or(DEFAULT_POINTS)
+    }
+
+    // 练习所属分类：未配置时取所在目录名，直接位于 exercises/ 下的练习（如 quiz1）取去掉序号的名称
+    pub fn category(&self) -> String {
+        if let Some(category) = &self.category {
+            return category.clone();
+        }
+        match self.path.parent().and_then(|dir| dir.file_name()) {
+            Some(dir) if dir != "exercises" => dir.to_string_lossy().to_string(),
+            _ => self.name.trim_end_matches(|c: char| c.is_ascii_digit()).to_string(),
+        }
+    }
 }
 
 // 实现 CompiledExercise 的运行方法
 impl<'a> CompiledExercise<'a> {
     pub fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        self.exercise.run()
+        let result = match &self.cached {
+            Some(output) if output.outcome == Outcome::Passed => return Ok(output.clone()),
+            Some(output) => return Err(output.clone()),
+            None => self.exercise.run(),
+        };
+
+        // 超时和内存超限可能只是机器繁忙导致的，不写入缓存
+        let output = match &result {
+            Ok(output) | Err(output) => output,
+        };
+        if !matches!(output.outcome, Outcome::TimedOut | Outcome::OutOfMemory) {
+            cache::store(
+                &self.cache_key,
+                &CacheEntry {
+                    compiled: true,
+                    output: output.clone(),
+                },
+            );
+        }
+        result
+    }
+}
+
+// 为 Outcome 实现 Display trait（用于报告中的说明文字）
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let text = match self {
+            Outcome::Passed => "passed",
+            Outcome::CompileError => "compile error",
+            Outcome::TestFailure => "test failure",
+            Outcome::RuntimeError => "runtime error",
+            Outcome::TimedOut => "timed out",
+            Outcome::OutOfMemory => "out of memory",
+            Outcome::NotDone => "still marked I AM NOT DONE",
+            Outcome::Tampered => "protected code was modified",
+            Outcome::MutantSurvived => "tests missed a mutant",
+            Outcome::WrongOutput => "wrong output",
+            Outcome::UnexpectedlyCompiled => "compiled, but should not",
+            Outcome::GraderError => "grader error",
+            Outcome::Unknown => "unknown, report predates outcomes",
+        };
+        write!(f, "{text}")
     }
 }
 
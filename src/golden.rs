@@ -0,0 +1,182 @@
+use console::style;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// How the stdout of a `compile` exercise is compared to its expected output
+#[derive(Deserialize, Copy, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMatch {
+    /// byte for byte
+    Exact,
+    /// ignoring trailing whitespace on each line and blank lines at either end
+    #[default]
+    Trimmed,
+    /// the expected output is a regex the whole output must match,
+    /// trailing whitespace aside
+    Regex,
+    /// the same set of lines in any order, each trimmed, blank lines ignored
+    Lines,
+}
+
+/// Reject expected outputs that can never match, i.e. invalid regexes
+pub fn validate(expected: &str, mode: OutputMatch) -> Result<(), String> {
+    match mode {
+        OutputMatch::Regex => regex(expected).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// Compare `actual` to `expected`. On a mismatch the error explains it,
+/// as a colored line diff for `exact` and `trimmed`.
+pub fn check(expected: &str, actual: &str, mode: OutputMatch) -> Result<(), String> {
+    match mode {
+        OutputMatch::Exact if expected == actual => Ok(()),
+        OutputMatch::Exact => Err(diff(expected, actual)),
+        OutputMatch::Trimmed => {
+            let (expected, actual) = (trim(expected), trim(actual));
+            if expected == actual {
+                Ok(())
+            } else {
+                Err(diff(&expected, &actual))
+            }
+        }
+        OutputMatch::Regex => {
+            let re = regex(expected)?;
+            if re.is_match(actual.trim_end()) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "expected output matching\n{}\nbut got\n{}",
+                    style(expected).green(),
+                    style(actual).red()
+                ))
+            }
+        }
+        OutputMatch::Lines => {
+            let lines = |text: &str| -> BTreeSet<String> {
+                text.lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            };
+            let (expected, actual) = (lines(expected), lines(actual));
+            if expected == actual {
+                return Ok(());
+            }
+            let mut message = String::new();
+            for line in expected.difference(&actual) {
+                let _ = writeln!(message, "{}", style(format!("- {line}")).red());
+            }
+            for line in actual.difference(&expected) {
+                let _ = writeln!(message, "{}", style(format!("+ {line}")).green());
+            }
+            Err(message)
+        }
+    }
+}
+
+// The expected output as a regex anchored at both ends
+fn regex(expected: &str) -> Result<Regex, String> {
+    Regex::new(&format!(r"\A(?:{expected})\z"))
+        .map_err(|e| format!("invalid expected output regex: {e}"))
+}
+
+// Drop trailing whitespace from every line, and blank lines at either end
+fn trim(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+// A line diff of the expected and the actual output: missing lines
+// are prefixed with `-` in red, unexpected ones with `+` in green
+fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.split('\n').collect();
+    let new: Vec<&str> = actual.split('\n').collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut message = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            let _ = writeln!(message, "  {}", old[i]);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            let _ = writeln!(message, "{}", style(format!("- {}", old[i])).red());
+            i += 1;
+        } else {
+            let _ = writeln!(message, "{}", style(format!("+ {}", new[j])).green());
+            j += 1;
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: String) -> String {
+        console::strip_ansi_codes(&text).into_owned()
+    }
+
+    #[test]
+    fn diff_marks_missing_and_unexpected_lines() {
+        let message = plain(diff("a\nb\nc", "a\nx\nc"));
+        assert_eq!(message, "  a\n- b\n+ x\n  c\n");
+    }
+
+    #[test]
+    fn diff_keeps_the_longest_common_lines() {
+        let message = plain(diff("a\nb\nc\nd", "b\nc\nd\ne"));
+        assert_eq!(message, "- a\n  b\n  c\n  d\n+ e\n");
+    }
+
+    #[test]
+    fn trim_drops_trailing_whitespace_and_blank_lines_at_either_end() {
+        assert_eq!(trim("\n\nfirst  \n\n  second\t\n\n"), "first\n\n  second");
+    }
+
+    #[test]
+    fn trimmed_ignores_trailing_whitespace() {
+        assert!(check("hello\nworld\n", "hello  \nworld\n\n", OutputMatch::Trimmed).is_ok());
+        assert!(check("hello", "  hello", OutputMatch::Trimmed).is_err());
+    }
+
+    #[test]
+    fn exact_compares_byte_for_byte() {
+        assert!(check("hello\n", "hello\n", OutputMatch::Exact).is_ok());
+        assert!(check("hello\n", "hello", OutputMatch::Exact).is_err());
+    }
+
+    #[test]
+    fn lines_ignores_order_indentation_and_blank_lines() {
+        assert!(check("one\ntwo\nthree", "three\n\n  one\ntwo  ", OutputMatch::Lines).is_ok());
+    }
+
+    #[test]
+    fn lines_reports_missing_and_unexpected_lines() {
+        let message = plain(check("one\ntwo", "two\nthree", OutputMatch::Lines).unwrap_err());
+        assert_eq!(message, "- one\n+ three\n");
+    }
+
+    #[test]
+    fn regex_must_match_the_whole_output() {
+        assert!(check(r"\d+ apples", "12 apples\n", OutputMatch::Regex).is_ok());
+        assert!(check(r"\d+ apples", "12 apples and pears", OutputMatch::Regex).is_err());
+        assert!(validate("(unclosed", OutputMatch::Regex).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::deadline::{parse_deadline, submitted_at};
+use crate::exercise::{Exercise, Mode, Outcome, HIDDEN_TESTS_MODULE};
+use crate::hidden::HiddenTests;
+use crate::report::{ExerciseResult, TestCounts};
+use crate::tamper::{changed_regions, exercise_fingerprints, region_fingerprints, Fingerprints, TamperPolicy};
+use crate::variant::render;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Instant;
+
+// How much of an exercise's stdout and stderr is kept in the report
+const OUTPUT_LIMIT: usize = 4096;
+
+// What grading does with exercises that still carry the `I AM NOT DONE` marker
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum MarkerPolicy {
+    /// grade the exercise as if the marker wasn't there
+    Ignore,
+    /// grade as usual, but call the marker out on the console
+    Warn,
+    /// fail the exercise even if it compiles and its tests pass
+    #[default]
+    Fail,
+}
+
+impl FromStr for MarkerPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(MarkerPolicy::Ignore),
+            "warn" => Ok(MarkerPolicy::Warn),
+            "fail" => Ok(MarkerPolicy::Fail),
+            _ => Err(format!(
+                "unknown marker policy `{s}`, expected one of: ignore, warn, fail"
+            )),
+        }
+    }
+}
+
+// Settings shared by every exercise of a grading run
+#[derive(Default)]
+pub struct GradeOptions {
+    pub marker_policy: MarkerPolicy,
+    /// how to treat exercises whose protected regions changed, if checked at all
+    pub tamper_policy: Option<TamperPolicy>,
+    pub fingerprints: Fingerprints,
+    /// grader-only tests to splice into test exercises, none when not given
+    pub hidden_tests: HiddenTests,
+    /// the student's seed, to re-derive the variants of templated exercises
+    pub seed: Option<String>,
+}
+
+// Compile and run the given Exercise without any interactive output,
+// recording how it went for the grading report.
+// `position` is the 1-based position of the exercise in info.toml.
+pub fn grade(exercise: &Exercise, position: usize, options: &GradeOptions) -> ExerciseResult {
+    let start = Instant::now();
+    let mut output = match exercise.compile_for_grading(&options.hidden_tests) {
+        Ok(compilation) => compilation.run().unwrap_or_else(|output| output),
+        Err(output) => output,
+    };
+    // A templated exercise must keep the protected regions of the student's own variant,
+    // so tests can't be swapped for those of another seed. This is checked even without
+    // --tamper, and fails the exercise unless the tamper policy says otherwise.
+    let variant = match (&options.seed, &exercise.template) {
+        (Some(seed), Some(_)) => render(exercise, seed).ok(),
+        _ => None,
+    };
+    // Without the student's seed there is nothing to check the tests against,
+    // and a seed file in the student's repository can't be trusted
+    let missing_variant = exercise.template.is_some() && variant.is_none();
+    if missing_variant {
+        output.stderr.insert_str(
+            0,
+            "No seed to derive the student's variant from, pass it with --seed\n",
+        );
+    }
+    let canonical = match &variant {
+        Some(variant) => Some(exercise_fingerprints(exercise, variant)),
+        None => options.fingerprints.get(&exercise.name).cloned(),
+    };
+    let (tampered, tamper_policy) = match (&variant, &canonical) {
+        (Some(_), Some(canonical)) => (
+            changed_regions(exercise, canonical),
+            Some(options.tamper_policy.unwrap_or(TamperPolicy::Fail)),
+        ),
+        // An exercise the fingerprints don't mention had no protected regions
+        (None, canonical) if options.tamper_policy.is_some() => (
+            changed_regions(exercise, &canonical.clone().unwrap_or_default()),
+            options.tamper_policy,
+        ),
+        _ => (Vec::new(), options.tamper_policy),
+    };
+    let not_done_marker = !exercise.looks_done();
+    let outcome = if missing_variant {
+        Outcome::GraderError
+    } else if !tampered.is_empty() && tamper_policy == Some(TamperPolicy::Fail) {
+        Outcome::Tampered
+    } else if output.outcome == Outcome::Passed
+        && not_done_marker
+        && options.marker_policy == MarkerPolicy::Fail
+    {
+        Outcome::NotDone
+    } else {
+        output.outcome
+    };
+
+    // Test exercises earn partial credit for each passing course test, hidden ones
+    // included; the report lists visible and hidden tests separately
+    let hidden_count = options.hidden_tests.files(&exercise.name).len();
+    let course_tests = match exercise.mode {
+        Mode::Test | Mode::BuildScript | Mode::Doctest => {
+            course_tests(exercise, &output.tests, canonical.as_ref(), hidden_count)
+        }
+        _ => BTreeMap::new(),
+    };
+    let all_tests = counts(course_tests.values());
+    let hidden_tests = match (exercise.mode, all_tests) {
+        (Mode::Test, Some(_)) if hidden_count > 0 => {
+            let hidden = course_tests
+                .iter()
+                .filter(|(name, _)| is_hidden_test(hidden_count, name))
+                .map(|(_, passed)| passed);
+            Some(counts(hidden).unwrap_or(TestCounts { passed: 0, total: 0 }))
+        }
+        _ => None,
+    };
+    let tests = match (all_tests, hidden_tests) {
+        (Some(all), Some(hidden)) => Some(TestCounts {
+            passed: all.passed.saturating_sub(hidden.passed),
+            total: all.total.saturating_sub(hidden.total),
+        }),
+        (all, _) => all,
+    };
+    // Mutation exercises earn partial credit for each mutant their tests catch
+    let mutants = match exercise.mode {
+        Mode::Mutation => counts(output.mutants.values()),
+        _ => None,
+    };
+    let points = exercise.points();
+    let mut score = match (outcome, all_tests, mutants) {
+        (Outcome::Passed, _, _) => points,
+        (Outcome::TestFailure, Some(tests), _) if tests.total > 0 => {
+            points * tests.passed as f64 / tests.total as f64
+        }
+        (Outcome::MutantSurvived, _, Some(mutants)) if mutants.total > 0 => {
+            points * mutants.passed as f64 / mutants.total as f64
+        }
+        _ => 0.0,
+    };
+
+    // Exercises submitted after their deadline lose part of their score
+    let deadline = exercise
+        .deadline
+        .as_deref()
+        .and_then(|deadline| parse_deadline(deadline).ok());
+    let submitted_at = deadline.and_then(|_| submitted_at(&exercise.path));
+    let late = matches!(
+        (deadline, submitted_at),
+        (Some(deadline), Some(submitted)) if submitted > deadline
+    );
+    if late {
+        score *= 1.0 - exercise.late_penalty.unwrap_or(0.0).clamp(0.0, 1.0);
+    }
+
+    ExerciseResult {
+        name: exercise.name.clone(),
+        path: exercise.path.display().to_string(),
+        result: outcome == Outcome::Passed,
+        outcome,
+        position,
+        duration_ms: start.elapsed().as_millis() as u64,
+        stdout: truncate_output(&output.stdout),
+        stderr: truncate_output(&output.stderr),
+        category: exercise.category(),
+        points,
+        score,
+        tests,
+        hidden_tests,
+        mutants,
+        tampered,
+        not_done_marker,
+        deadline: exercise.deadline.clone(),
+        submitted_at,
+        late,
+    }
+}
+
+// The tests partial credit is based on. Those are the tests listed in the exercise's
+// `graded_tests`, or else the tests in the course's test modules, as long as those
+// still match their fingerprint, plus the hidden tests. Tests the student added
+// earn nothing, and neither does anything when the course's tests aren't known.
+fn course_tests(
+    exercise: &Exercise,
+    tests: &BTreeMap<String, bool>,
+    canonical: Option<&BTreeMap<String, String>>,
+    hidden_count: usize,
+) -> BTreeMap<String, bool> {
+    if !exercise.graded_tests.is_empty() {
+        // A listed test the student removed counts as failed
+        return exercise
+            .graded_tests
+            .iter()
+            .map(|name| (name.clone(), tests.get(name).copied().unwrap_or(false)))
+            .collect();
+    }
+    let current = region_fingerprints(&exercise.source());
+    let unchanged_module = |name: &str| {
+        let Some((module, _)) = name.split_once("::") else {
+            return false;
+        };
+        let region = format!("mod {module}");
+        canonical.is_some_and(|canonical| {
+            canonical.get(&region).is_some_and(|hash| current.get(&region) == Some(hash))
+        })
+    };
+    tests
+        .iter()
+        .filter(|(name, _)| is_hidden_test(hidden_count, name) || unchanged_module(name))
+        .map(|(name, passed)| (name.clone(), *passed))
+        .collect()
+}
+
+// Whether the test is in one of the `hidden_count` modules `Exercise::hidden_test_source` splices in
+fn is_hidden_test(hidden_count: usize, name: &str) -> bool {
+    (0..hidden_count)
+        .any(|index| name.starts_with(&format!("{HIDDEN_TESTS_MODULE}_{index}::")))
+}
+
+// Count how many of the recorded tests passed (or mutants were caught),
+// None if nothing was recorded. The results come from the test harness' own
+// records rather than the exercise's output, which the student controls.
+fn counts<'a>(results: impl Iterator<Item = &'a bool>) -> Option<TestCounts> {
+    let results: Vec<bool> = results.copied().collect();
+    if results.is_empty() {
+        return None;
+    }
+    Some(TestCounts {
+        passed: results.iter().filter(|passed| **passed).count(),
+        total: results.len(),
+    })
+}
+
+// Strip terminal colors and cut the output down to OUTPUT_LIMIT bytes
+fn truncate_output(output: &str) -> String {
+    let output = console::strip_ansi_codes(output);
+    if output.len() <= OUTPUT_LIMIT {
+        return output.into_owned();
+    }
+    let mut end = OUTPUT_LIMIT;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n... ({} more bytes truncated)",
+        &output[..end],
+        output.len() - end
+    )
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Where `cicvverify` reads hidden tests from when not given `--hidden-tests`
+pub const HIDDEN_TESTS_ENV: &str = "RUSTLINGS_HIDDEN_TESTS";
+
+/// The file listing the hidden tests of each exercise, at the root of the hidden tests
+const MANIFEST: &str = "hidden_tests.toml";
+
+/// Grader-only tests, kept out of the students' repositories: a directory or an
+/// http(s) URL whose `hidden_tests.toml` maps exercise names to test files,
+/// given relative to the same directory or URL
+#[derive(Default)]
+pub struct HiddenTests {
+    root: String,
+    files: BTreeMap<String, Vec<String>>,
+}
+
+impl HiddenTests {
+    /// Read the list of hidden tests; the test files themselves are read
+    /// as each exercise is graded, possibly from another working directory
+    pub fn load(root: &str) -> Result<HiddenTests, Box<dyn Error>> {
+        let root = if is_url(root) {
+            root.trim_end_matches('/').to_string()
+        } else {
+            fs::canonicalize(root)?.display().to_string()
+        };
+        let files = toml::from_str(&read_location(&join(&root, MANIFEST))?)?;
+        Ok(HiddenTests { root, files })
+    }
+
+    /// The hidden test files of an exercise, relative to the root
+    pub fn files(&self, exercise: &str) -> &[String] {
+        self.files.get(exercise).map_or(&[], Vec::as_slice)
+    }
+
+    /// The contents of one of the hidden test files
+    pub fn read(&self, file: &str) -> Result<String, Box<dyn Error>> {
+        read_location(&join(&self.root, file))
+    }
+}
+
+/// Read a file, or download an http(s) URL
+pub fn read_location(location: &str) -> Result<String, Box<dyn Error>> {
+    if is_url(location) {
+        Ok(ureq::get(location).call()?.into_string()?)
+    } else {
+        Ok(fs::read_to_string(location)?)
+    }
+}
+
+fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+fn join(root: &str, file: &str) -> String {
+    if is_url(root) {
+        format!("{root}/{file}")
+    } else {
+        Path::new(root).join(file).display().to_string()
+    }
+}
@@ -1,19 +1,37 @@
 
-use crate::exercise::{Exercise, ExerciseList};
+use crate::class::ClassReport;
+use crate::deadline::parse_deadline;
+use crate::diff::ReportDiff;
+use crate::exercise::{remove_build_root, Exercise, ExerciseList, Mode, Outcome};
+use crate::grade::{grade, GradeOptions, MarkerPolicy};
+use crate::hidden::{HiddenTests, HIDDEN_TESTS_ENV};
+use crate::metadata::{student_identity, RunMetadata, SCHEMA_VERSION};
+use crate::pool::run_in_pool;
 use crate::project::RustAnalyzerProject;
+use crate::report::{ExerciseCheckList, ExerciseStatistics};
 use crate::run::{reset, run};
+use crate::select::Selection;
+use crate::similarity::{find_similar, SimilarityOptions};
+use crate::sign::{sign, signing_key, SIGNING_KEY_ENV};
+use crate::submit::{submit, Summary};
+use crate::tamper::{
+    fingerprint_exercises, load_fingerprints, save_fingerprints, TamperPolicy,
+    DEFAULT_FINGERPRINTS_PATH, FINGERPRINTS_ENV,
+};
+use crate::variant::{load_seed, render, save_seed};
 use crate::verify::verify;
+use crate::workspace::Workspace;
+use crate::writers::{write_report, JsonWriter, ReportFormat};
 use argh::FromArgs;
 use console::Emoji;
 use notify::DebouncedEvent;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
 use std::fs;
 use std::io::{self, prelude::*};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -23,10 +41,28 @@ use std::time::{UNIX_EPOCH, SystemTime};
 #[macro_use]
 mod ui;
 
+mod cache;
+mod class;
+mod deadline;
+mod diff;
 mod exercise;
+mod golden;
+mod grade;
+mod hidden;
+mod metadata;
+mod pool;
 mod project;
+mod report;
 mod run;
+mod select;
+mod sign;
+mod similarity;
+mod submit;
+mod tamper;
+mod variant;
 mod verify;
+mod workspace;
+mod writers;
 
 // In sync with crate version
 const VERSION: &str = "5.5.1";
@@ -37,6 +73,10 @@ struct Args {
     /// show outputs from the test exercises
     #[argh(switch)]
     nocapture: bool,
+    /// compile and run every exercise from scratch instead of reusing cached results
+    /// (cicvverify always does)
+    #[argh(switch)]
+    no_cache: bool,
     /// show the executable version
     #[argh(switch, short = 'v')]
     version: bool,
@@ -54,17 +94,261 @@ enum Subcommands {
     Hint(HintArgs),
     List(ListArgs),
     Lsp(LspArgs),
-    CicvVerify(CicvVerifyArgs)
+    CicvVerify(CicvVerifyArgs),
+    Fingerprint(FingerprintArgs),
+    Report(ReportArgs),
+    Submit(SubmitArgs),
+    ClassReport(ClassReportArgs),
+    Similarity(SimilarityArgs),
+    Init(InitArgs),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "cicvverify", description = "cicvverify")]
-struct CicvVerifyArgs {}
+struct CicvVerifyArgs {
+    /// number of exercises to grade in parallel (defaults to the number of CPUs)
+    #[argh(option, short = 'j')]
+    jobs: Option<usize>,
+    /// report format: json (default), junit, tap or github
+    #[argh(option, default = "ReportFormat::Json")]
+    format: ReportFormat,
+    /// where to write the report, `-` for stdout
+    /// (defaults to .github/result/check_result.<ext>, or stdout for github)
+    #[argh(option, short = 'o')]
+    output: Option<String>,
+    /// check test modules and protected regions against the course's
+    /// fingerprints, and either flag or fail exercises that changed them
+    #[argh(option)]
+    tamper: Option<TamperPolicy>,
+    /// fingerprints of the course's starter code, as a file or an http(s) URL
+    /// kept out of the students' repositories (defaults to $RUSTLINGS_FINGERPRINTS)
+    #[argh(option)]
+    fingerprints: Option<String>,
+    /// grader-only tests, as a directory or an http(s) URL holding hidden_tests.toml,
+    /// kept out of the students' repositories (defaults to $RUSTLINGS_HIDDEN_TESTS)
+    #[argh(option)]
+    hidden_tests: Option<String>,
+    /// what to do with exercises still marked `I AM NOT DONE`:
+    /// ignore, warn or fail (default)
+    #[argh(option, default = "MarkerPolicy::Fail")]
+    marker: MarkerPolicy,
+    /// seed of the student being graded, required for templated exercises
+    /// (the one `rustlings init` recorded comes from the student's repository,
+    /// so it isn't trusted)
+    #[argh(option)]
+    seed: Option<String>,
+    /// name of the student being graded (defaults to $RUSTLINGS_STUDENT,
+    /// $GITHUB_ACTOR or `git config user.name`)
+    #[argh(option)]
+    student: Option<String>,
+    /// email of the student being graded (defaults to `git config user.email`,
+    /// but only when the name also comes from `git config user.name`)
+    #[argh(option)]
+    student_email: Option<String>,
+    /// only grade exercises whose name matches this glob, e.g. `algorithm*` (repeatable)
+    #[argh(option)]
+    name: Vec<String>,
+    /// only grade exercises in this directory, e.g. `exercises/structs` (repeatable)
+    #[argh(option)]
+    dir: Vec<String>,
+    /// only grade exercises in this category (repeatable)
+    #[argh(option)]
+    category: Vec<String>,
+    /// start at this exercise, in `info.toml` order
+    #[argh(option)]
+    from: Option<String>,
+    /// stop after this exercise, in `info.toml` order
+    #[argh(option)]
+    to: Option<String>,
+    /// only grade exercises that failed in this earlier report
+    #[argh(option)]
+    failed_in: Option<String>,
+    /// grade this git revision instead of the working tree,
+    /// e.g. the last commit before the deadline
+    #[argh(option)]
+    rev: Option<String>,
+    /// grade the checkout or extracted submission in this directory
+    /// instead of the current one
+    #[argh(option)]
+    workspace: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "fingerprint")]
+/// Records fingerprints of every exercise's test modules and protected regions;
+/// run it in a checkout of the course's starter code
+struct FingerprintArgs {
+    /// where to write the fingerprints (defaults to fingerprints.toml)
+    #[argh(option, short = 'o', default = "DEFAULT_FINGERPRINTS_PATH.to_string()")]
+    output: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "report")]
+/// Works with grading reports written by `cicvverify`
+struct ReportArgs {
+    #[argh(subcommand)]
+    nested: ReportSubcommands,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand)]
+enum ReportSubcommands {
+    Diff(ReportDiffArgs),
+    Sign(ReportSignArgs),
+    Verify(ReportVerifyArgs),
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "diff")]
+/// Shows what changed between two grading reports
+struct ReportDiffArgs {
+    #[argh(positional)]
+    /// the earlier report
+    old: String,
+    #[argh(positional)]
+    /// the later report
+    new: String,
+    /// how many times slower an exercise must get to count as a timing regression
+    #[argh(option, default = "2.0")]
+    slowdown: f64,
+    /// exit with status 1 if anything regressed
+    #[argh(switch)]
+    fail_on_regression: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "sign")]
+/// Signs a JSON grading report with $RUSTLINGS_SIGNING_KEY
+struct ReportSignArgs {
+    #[argh(positional)]
+    /// the report to sign
+    report: String,
+    /// where to write the signed report (defaults to overwriting the report)
+    #[argh(option, short = 'o')]
+    output: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "verify")]
+/// Checks the signature of a grading report against $RUSTLINGS_SIGNING_KEY
+struct ReportVerifyArgs {
+    #[argh(positional)]
+    /// the report to check
+    report: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "class-report")]
+/// Aggregates the grading reports of a whole class into CSV and HTML
+struct ClassReportArgs {
+    #[argh(positional)]
+    /// directory containing one `cicvverify` JSON report per student
+    reports: String,
+    /// where to write exercises.csv, students.csv and index.html
+    /// (defaults to class-report)
+    #[argh(option, short = 'o', default = "String::from(\"class-report\")")]
+    output: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "similarity")]
+/// Finds submissions whose solutions look copied from each other
+struct SimilarityArgs {
+    #[argh(positional)]
+    /// submission directories, each laid out like the course
+    submissions: Vec<String>,
+    /// report pairs at least this similar, from 0 to 1 (defaults to 0.8)
+    #[argh(option, default = "0.8")]
+    threshold: f64,
+    /// only compare exercises whose name matches this glob, e.g. `algorithm*` (repeatable)
+    #[argh(option)]
+    name: Vec<String>,
+    /// a checkout of the starter code; code shared with it is ignored
+    #[argh(option)]
+    base: Option<String>,
+    /// number of tokens hashed together (defaults to 5)
+    #[argh(option, short = 'k', default = "5")]
+    kgram: usize,
+    /// number of hashes each fingerprint is picked from (defaults to 4)
+    #[argh(option, default = "4")]
+    window: usize,
+    /// print the matched lines of both submissions
+    #[argh(switch)]
+    show_matches: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "init")]
+/// Generates this student's variants of the templated exercises
+struct InitArgs {
+    /// the student's seed, e.g. their student ID
+    #[argh(option)]
+    seed: String,
+    /// overwrite exercises that were already generated, losing any work on them
+    #[argh(switch)]
+    force: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "submit")]
+/// Sends the course summary of a grading report to the classroom backend
+struct SubmitArgs {
+    /// the grading report to summarize (defaults to .github/result/check_result.json)
+    #[argh(option, default = "ReportFormat::Json.default_output().to_string()")]
+    report: String,
+    /// where to POST the summary (defaults to $RUSTLINGS_SUBMIT_URL)
+    #[argh(option)]
+    endpoint: Option<String>,
+    /// sent in the `token` header (defaults to $RUSTLINGS_SUBMIT_TOKEN)
+    #[argh(option)]
+    token: Option<String>,
+    /// the course to submit to (defaults to $RUSTLINGS_COURSE_ID)
+    #[argh(option)]
+    course_id: Option<i64>,
+    /// the channel the submission comes from (defaults to github)
+    #[argh(option, default = "String::from(\"github\")")]
+    channel: String,
+    /// extra data passed through to the backend
+    #[argh(option, default = "String::new()")]
+    ext: String,
+    /// the student's name (defaults to the one in the report)
+    #[argh(option)]
+    name: Option<String>,
+    /// how many times to retry a failed submission (defaults to 3)
+    #[argh(option, default = "3")]
+    retries: u32,
+    /// also write the summary to this file
+    #[argh(option, short = 'o')]
+    output: Option<String>,
+    /// print the summary instead of sending it
+    #[argh(switch)]
+    dry_run: bool,
+}
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "verify")]
 /// Verifies all exercises according to the recommended order
-struct VerifyArgs {}
+struct VerifyArgs {
+    /// only verify exercises whose name matches this glob, e.g. `algorithm*` (repeatable)
+    #[argh(option)]
+    name: Vec<String>,
+    /// only verify exercises in this directory, e.g. `exercises/structs` (repeatable)
+    #[argh(option)]
+    dir: Vec<String>,
+    /// only verify exercises in this category (repeatable)
+    #[argh(option)]
+    category: Vec<String>,
+    /// start at this exercise, in `info.toml` order
+    #[argh(option)]
+    from: Option<String>,
+    /// stop after this exercise, in `info.toml` order
+    #[argh(option)]
+    to: Option<String>,
+    /// only verify exercises that failed in this earlier report
+    #[argh(option)]
+    failed_in: Option<String>,
+}
 
 #[derive(FromArgs, PartialEq, Debug)]
 #[argh(subcommand, name = "watch")]
@@ -129,29 +413,7 @@ struct ListArgs {
     solved: bool,
 }
 
-#[derive(Deserialize, Serialize)]
-pub struct ExerciseCheckList {
-    pub exercises: Vec<ExerciseResult>,
-    pub user_name: Option<String>,
-    pub statistics: ExerciseStatistics
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct ExerciseResult {
-    pub name: String,
-    pub result: bool
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct  ExerciseStatistics {
-    pub total_exercations: usize,
-    pub total_succeeds: usize,
-    pub total_failures: usize,
-    pub total_time: u32,
-}
-
-#[tokio::main]
-async fn main() {
+fn main() {
     let args: Args = argh::from_env();
 
     if args.version {
@@ -163,7 +425,21 @@ async fn main() {
         println!("\n{WELCOME}\n");
     }
 
-    if !Path::new("info.toml").exists() {
+    // Subcommands that only work on existing reports don't need info.toml
+    let command = match args.nested {
+        Some(Subcommands::Report(subargs)) => return report(subargs),
+        Some(Subcommands::ClassReport(subargs)) => return class_report(subargs),
+        Some(Subcommands::Submit(subargs)) => return submit_report(subargs),
+        command => command,
+    };
+
+    // 在 --rev 或 --workspace 指定的工作树中批改时，使用那里的 info.toml，
+    // 当前目录不必是 rustlings 目录
+    let grades_elsewhere = matches!(
+        &command,
+        Some(Subcommands::CicvVerify(subargs)) if subargs.rev.is_some() || subargs.workspace.is_some()
+    );
+    if !grades_elsewhere && !Path::new("info.toml").exists() {
         println!(
             "{} must be run from the rustlings directory",
             std::env::current_exe().unwrap().to_str().unwrap()
@@ -179,11 +455,25 @@ async fn main() {
         std::process::exit(1);
     }
 
-    let toml_str = &fs::read_to_string("info.toml").unwrap();
-    let exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
+    let exercises = if grades_elsewhere {
+        Vec::new()
+    } else {
+        let toml_str = fs::read_to_string("info.toml").unwrap_or_else(|e| {
+            println!("Failed to read info.toml: {e}");
+            std::process::exit(1);
+        });
+        match toml::from_str::<ExerciseList>(&toml_str) {
+            Ok(list) => list.into_exercises(),
+            Err(e) => {
+                println!("Failed to parse info.toml: {e}");
+                std::process::exit(1);
+            }
+        }
+    };
     let verbose = args.nocapture;
+    cache::set_enabled(!args.no_cache);
 
-    let command = args.nested.unwrap_or_else(|| {
+    let command = command.unwrap_or_else(|| {
         println!("{DEFAULT_OUT}\n");
         std::process::exit(0);
     });
@@ -199,7 +489,7 @@ async fn main() {
                 let filter_cond = filters
                     .split(',')
                     .filter(|f| !f.trim().is_empty())
-                    .any(|f| e.name.contains(&f) || fname.contains(&f));
+                    .any(|f| e.name.contains(f) || fname.contains(f));
                 let status = if e.looks_done() {
                     exercises_done += 1;
                     "Done"
@@ -246,7 +536,9 @@ async fn main() {
 
         Subcommands::Run(subargs) => {
             let exercise = find_exercise(&subargs.name, &exercises);
-            run(exercise, verbose).unwrap_or_else(|_| std::process::exit(1));
+            let status = run(exercise, verbose);
+            remove_build_root();
+            status.unwrap_or_else(|_| std::process::exit(1));
         }
 
         Subcommands::Reset(subargs) => {
@@ -261,85 +553,296 @@ async fn main() {
             println!("{}", exercise.hint);
         }
 
-        Subcommands::Verify(_subargs) => {
-            verify(&exercises, (0, exercises.len()), verbose, false)
-                .unwrap_or_else(|_| std::process::exit(1));
+        Subcommands::Verify(subargs) => {
+            let selection = Selection {
+                names: subargs.name,
+                dirs: subargs.dir,
+                categories: subargs.category,
+                from: subargs.from,
+                to: subargs.to,
+                failed_in: subargs.failed_in,
+            };
+            let selected = select_exercises(&selection, &exercises);
+            let status = verify(selected.iter().map(|(_, e)| *e), (0, selected.len()), verbose, false);
+            remove_build_root();
+            status.unwrap_or_else(|_| std::process::exit(1));
         }
 
-        Subcommands::CicvVerify(_subargs) => {
-            // let toml_str = &fs::read_to_string("info.toml").unwrap();
-            // exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
+        Subcommands::CicvVerify(subargs) => {
+            // 学生的代码能读到周围所有进程的环境变量（例如通过 /proc），批改时不能有签名密钥，
+            // 报告在批改结束后的单独步骤中用 `rustlings report sign` 签名
+            if signing_key().is_some() {
+                println!(
+                    "${SIGNING_KEY_ENV} is set, but grading runs student code that could read it. \
+                     Grade without it, then sign the report with `rustlings report sign`"
+                );
+                std::process::exit(1);
+            }
+            // 缓存中的结果没有签名，学生可以随意改写，批改时一律重新编译运行，也不写入缓存
+            cache::set_enabled(false);
             let now_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-            let rights = Arc::new(Mutex::new(0));
-            let alls = exercises.len();
-
-            let exercise_check_list =  Arc::new(Mutex::new(
-                ExerciseCheckList {
-                    exercises: vec![], 
-                    user_name:  None, 
-                    statistics: ExerciseStatistics { 
-                        total_exercations: alls, 
-                        total_succeeds: 0, 
-                        total_failures: 0, 
-                        total_time: 0, 
+            let rights = AtomicUsize::new(0);
+            let output = subargs
+                .output
+                .unwrap_or_else(|| subargs.format.default_output().to_string());
+            // 报告写到标准输出时，批改进度改为写到标准错误，以免混进报告
+            let report_to_stdout = output == "-";
+            macro_rules! progress {
+                ($($arg:tt)*) => {
+                    if report_to_stdout {
+                        eprintln!($($arg)*)
+                    } else {
+                        println!($($arg)*)
                     }
+                };
+            }
+            let jobs = subargs.jobs.unwrap_or_else(pool::default_jobs);
+            let mut options = GradeOptions {
+                marker_policy: subargs.marker,
+                tamper_policy: subargs.tamper,
+                ..GradeOptions::default()
+            };
+            // 指纹只从明确指定的位置读取：被批改的仓库中的指纹文件学生可以随意重新生成。
+            // 部分得分只计入指纹中课程自带的测试，因此即使不检查篡改，指定了指纹也要读取
+            let fingerprints = subargs
+                .fingerprints
+                .or_else(|| std::env::var(FINGERPRINTS_ENV).ok().filter(|source| !source.is_empty()));
+            match fingerprints {
+                Some(source) => {
+                    options.fingerprints = load_fingerprints(&source).unwrap_or_else(|e| {
+                        println!("Failed to read fingerprints from {source}: {e}");
+                        std::process::exit(1);
+                    });
                 }
-            ));
-
-            let mut tasks = vec![];
-            for exercise in exercises {
-                let now_start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                let inner_exercise = exercise;
-                let c_mutex = Arc::clone(&rights);
-                let exercise_check_list_ref = Arc::clone(&exercise_check_list);
-                let _verbose = verbose.clone();
-                let t = tokio::task::spawn( async move {
-                    match run(&inner_exercise, true) {
-                    // match verify(vec![&inner_exercise], (0, 1), true, true) {
-                        Ok(_) => {
-                            *c_mutex.lock().unwrap() += 1;
-                            println!("{}执行成功", inner_exercise.name);
-                            println!("总的题目数: {}", alls);
-                            println!("当前做正确的题目数: {}", *c_mutex.lock().unwrap());
-                            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            println!("当前修改试卷耗时: {} s", now_end - now_start);
-                            exercise_check_list_ref.lock().unwrap().exercises.push(ExerciseResult{ 
-                                name: inner_exercise.name, result: true,
-                            });
-                            exercise_check_list_ref.lock().unwrap().statistics.total_succeeds += 1;
-                        },
-                        Err(_) => {
-                            println!("{}执行失败", inner_exercise.name);
-                            println!("总的题目数: {}", alls);
-                            println!("当前做正确的题目数: {}", *c_mutex.lock().unwrap());
-                            let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                            println!("当前修改试卷耗时: {} s", now_end - now_start);
-                            exercise_check_list_ref.lock().unwrap().exercises.push(ExerciseResult{ 
-                                name: inner_exercise.name, result: false,
-                            });
-                            exercise_check_list_ref.lock().unwrap().statistics.total_failures += 1;
-                        }
-                    }
+                None if subargs.tamper.is_some() => {
+                    println!("--tamper needs the course's fingerprints, pass them with --fingerprints or ${FINGERPRINTS_ENV}");
+                    std::process::exit(1);
+                }
+                None => {}
+            }
+            // 隐藏测试同样只从明确指定的位置读取，不使用被批改仓库中的文件
+            let hidden_tests = subargs
+                .hidden_tests
+                .or_else(|| std::env::var(HIDDEN_TESTS_ENV).ok().filter(|source| !source.is_empty()));
+            if let Some(source) = hidden_tests {
+                options.hidden_tests = HiddenTests::load(&source).unwrap_or_else(|e| {
+                    println!("Failed to read hidden tests from {source}: {e}");
+                    std::process::exit(1);
                 });
-                tasks.push(t);
             }
-            for task in tasks { task.await.unwrap(); }
+
+            // 指定了 --rev 或 --workspace 时，在临时的 git 工作树或副本中批改，
+            // 不改动当前目录下的练习；指纹和报告路径仍相对于当前目录
+            let original_dir = std::env::current_dir().unwrap();
+            let failed_in = subargs
+                .failed_in
+                .map(|path| original_dir.join(path).to_string_lossy().to_string());
+            let source = subargs.workspace.as_deref().map(Path::new);
+            let workspace = match (&subargs.rev, source) {
+                (Some(rev), source) => Some(Workspace::checkout(source.unwrap_or(Path::new(".")), rev)),
+                (None, Some(source)) => Some(Workspace::copy(source)),
+                (None, None) => None,
+            }
+            .transpose()
+            .unwrap_or_else(|e| {
+                println!("{e}");
+                std::process::exit(1);
+            });
+            let exercises = match workspace.as_ref().map(|workspace| {
+                std::env::set_current_dir(workspace.path()).unwrap();
+                fs::read_to_string("info.toml")
+            }) {
+                Some(Ok(toml_str)) => match toml::from_str::<ExerciseList>(&toml_str) {
+                    Ok(list) => list.into_exercises(),
+                    Err(e) => exit_with(&format!("Failed to parse info.toml in the workspace: {e}"), workspace, &original_dir),
+                },
+                Some(Err(_)) => exit_with("There is no info.toml in the workspace to grade", workspace, &original_dir),
+                None => exercises,
+            };
+            options.seed = subargs.seed;
+
+            let selection = Selection {
+                names: subargs.name,
+                dirs: subargs.dir,
+                categories: subargs.category,
+                from: subargs.from,
+                to: subargs.to,
+                failed_in,
+            };
+            let selected = match selection.apply(&exercises) {
+                Ok(selected) if selected.is_empty() => {
+                    exit_with("No exercises match the selection!", workspace, &original_dir)
+                }
+                Ok(selected) => selected,
+                Err(e) => exit_with(&e.to_string(), workspace, &original_dir),
+            };
+            let alls = selected.len();
+            // 先检查所有选中练习的配置，出错时清理工作树后再退出
+            let seed = options.seed.as_deref();
+            if let Err(e) = selected.iter().try_for_each(|(_, exercise)| validate_exercise(exercise, seed)) {
+                exit_with(&e, workspace, &original_dir);
+            }
+
+            // 每道题都在独立的构建目录中批改，结果按 info.toml 中的顺序返回
+            let results = run_in_pool(&selected, jobs, |_, (index, exercise)| {
+                let result = grade(exercise, index + 1, &options);
+                match result.outcome {
+                    Outcome::Passed => {
+                        rights.fetch_add(1, Ordering::SeqCst);
+                        progress!("{}执行成功", exercise.name);
+                    }
+                    Outcome::CompileError => progress!("{}编译失败", exercise.name),
+                    Outcome::TestFailure => progress!("{}测试未通过", exercise.name),
+                    Outcome::RuntimeError => progress!("{}执行失败", exercise.name),
+                    Outcome::TimedOut => progress!("{}执行超时", exercise.name),
+                    Outcome::OutOfMemory => progress!("{}内存超限", exercise.name),
+                    Outcome::NotDone => progress!("{}仍标记为 I AM NOT DONE", exercise.name),
+                    Outcome::Tampered => progress!("{}的测试代码被修改", exercise.name),
+                    Outcome::MutantSurvived => progress!("{}的测试没有发现全部错误实现", exercise.name),
+                    Outcome::WrongOutput => progress!("{}输出结果不正确", exercise.name),
+                    Outcome::UnexpectedlyCompiled => progress!("{}应当编译失败，却编译通过了", exercise.name),
+                    // 批改本身不会得出 Unknown，它只来自旧报告
+                    Outcome::GraderError | Outcome::Unknown => progress!("{}批改出错，需要人工检查", exercise.name),
+                }
+                if result.not_done_marker && subargs.marker == MarkerPolicy::Warn {
+                    progress!("{}仍标记为 I AM NOT DONE，请删除该标记", exercise.name);
+                }
+                if result.late {
+                    let deadline = result.deadline.as_deref().unwrap_or_default();
+                    progress!("{}迟交，截止时间为 {}", exercise.name, deadline);
+                }
+                if let (Some(tests), Some(hidden)) = (result.tests, result.hidden_tests) {
+                    progress!(
+                        "{}可见测试通过 {}/{}，隐藏测试通过 {}/{}",
+                        exercise.name, tests.passed, tests.total, hidden.passed, hidden.total
+                    );
+                }
+                if !result.tampered.is_empty() {
+                    progress!("{}中被修改的受保护区域: {}", exercise.name, result.tampered.join(", "));
+                }
+                if !result.result {
+                    progress!("{}", result.stdout);
+                    progress!("{}", result.stderr);
+                }
+                progress!("总的题目数: {}", alls);
+                progress!("当前做正确的题目数: {}", rights.load(Ordering::SeqCst));
+                progress!("当前修改试卷耗时: {} ms", result.duration_ms);
+                result
+            });
+            remove_build_root();
+
             let now_end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
             let total_time = now_end - now_start;
-            println!("===============================试卷批改完成,总耗时: {} s; ==================================", total_time);
-            let exercise_check_list_ref = Arc::clone(&exercise_check_list);
-            exercise_check_list_ref.lock().unwrap().statistics.total_time = total_time as u32;
-            let serialized = serde_json::to_string_pretty(&*exercise_check_list.lock().unwrap()).unwrap();
-            fs::write(".github/result/check_result.json", serialized).unwrap();
+            progress!("===============================试卷批改完成,总耗时: {} s; ==================================", total_time);
+            let (user_name, user_email) = student_identity(subargs.student, subargs.student_email);
+            let exercise_check_list = ExerciseCheckList {
+                schema_version: SCHEMA_VERSION,
+                user_name,
+                user_email,
+                statistics: ExerciseStatistics::new(&results, total_time as u32),
+                exercises: results,
+                metadata: Some(RunMetadata::collect(VERSION, now_start, now_end)),
+                signature: None,
+            };
+            std::env::set_current_dir(&original_dir).unwrap();
+            drop(workspace);
+            let statistics = &exercise_check_list.statistics;
+            for (category, subtotal) in &statistics.categories {
+                progress!("{}: {:.2} / {:.2}", category, subtotal.score, subtotal.max_score);
+            }
+            progress!("总得分: {:.2} / {:.2}", statistics.total_score, statistics.max_score);
+            if statistics.total_late > 0 {
+                progress!("迟交题目数: {}", statistics.total_late);
+            }
+            if let Err(e) = write_report(&*subargs.format.writer(), &exercise_check_list, &output) {
+                println!("Failed to write the report to {output}: {e}");
+                std::process::exit(1);
+            }
         },
 
+        Subcommands::Fingerprint(subargs) => {
+            let fingerprints = fingerprint_exercises(&exercises);
+            if let Err(e) = save_fingerprints(&subargs.output, &fingerprints) {
+                println!("Failed to write fingerprints to {}: {e}", subargs.output);
+                std::process::exit(1);
+            }
+            println!(
+                "Recorded fingerprints for {} exercises in {}",
+                fingerprints.len(),
+                subargs.output
+            );
+        }
+
+        Subcommands::Similarity(subargs) => {
+            let selection = Selection {
+                names: subargs.name,
+                ..Selection::default()
+            };
+            let selected: Vec<&Exercise> = select_exercises(&selection, &exercises)
+                .into_iter()
+                .map(|(_, exercise)| exercise)
+                .collect();
+            let submissions: Vec<PathBuf> = subargs.submissions.iter().map(PathBuf::from).collect();
+            let options = SimilarityOptions {
+                k: subargs.kgram,
+                window: subargs.window,
+                threshold: subargs.threshold,
+            };
+            let base = subargs.base.as_deref().map(Path::new);
+            let pairs = find_similar(&selected, &submissions, base, &options);
+            for pair in &pairs {
+                pair.print(subargs.show_matches);
+            }
+            println!(
+                "{} similar pairs among {} submissions",
+                pairs.len(),
+                submissions.len()
+            );
+        }
+
+        Subcommands::Init(subargs) => {
+            if let Some(seed) = load_seed() {
+                if seed != subargs.seed && !subargs.force {
+                    println!("The exercises were already generated for another seed, use --force to replace them");
+                    std::process::exit(1);
+                }
+            }
+            let mut generated = 0;
+            for exercise in exercises.iter().filter(|e| e.template.is_some()) {
+                if exercise.path.exists() && !subargs.force {
+                    println!("Keeping {}, it already exists", exercise.path.display());
+                    continue;
+                }
+                let written = render(exercise, &subargs.seed).and_then(|source| {
+                    if let Some(dir) = exercise.path.parent() {
+                        fs::create_dir_all(dir)?;
+                    }
+                    Ok(fs::write(&exercise.path, source)?)
+                });
+                if let Err(e) = written {
+                    println!("Failed to generate {}: {e}", exercise.name);
+                    std::process::exit(1);
+                }
+                generated += 1;
+            }
+            if let Err(e) = save_seed(&subargs.seed) {
+                println!("Failed to record the seed: {e}");
+                std::process::exit(1);
+            }
+            println!("Generated {generated} exercises for seed {}", subargs.seed);
+        }
+
+        Subcommands::Report(_) | Subcommands::ClassReport(_) | Subcommands::Submit(_) => {
+            unreachable!("dispatched before loading info.toml")
+        }
+
         Subcommands::Lsp(_subargs) => {
             let mut project = RustAnalyzerProject::new();
             project
                 .get_sysroot_src()
                 .expect("Couldn't find toolchain path, do you have `rustc` installed?");
             project
-                .exercises_to_json()
+                .exercises_to_json(&exercises)
                 .expect("Couldn't parse rustlings exercises files");
 
             if project.crates.is_empty() {
@@ -352,30 +855,217 @@ async fn main() {
             }
         }
 
-        Subcommands::Watch(_subargs) => match watch(&exercises, verbose, _subargs.success_hints) {
-            Err(e) => {
-                println!(
-                    "Error: Could not watch your progress. Error message was {:?}.",
-                    e
-                );
-                println!("Most likely you've run out of disk space or your 'inotify limit' has been reached.");
+        Subcommands::Watch(_subargs) => {
+            let status = watch(&exercises, verbose, _subargs.success_hints);
+            remove_build_root();
+            match status {
+                Err(e) => {
+                    println!(
+                        "Error: Could not watch your progress. Error message was {:?}.",
+                        e
+                    );
+                    println!("Most likely you've run out of disk space or your 'inotify limit' has been reached.");
+                    std::process::exit(1);
+                }
+                Ok(WatchStatus::Finished) => {
+                    println!(
+                        "{emoji} All exercises completed! {emoji}",
+                        emoji = Emoji("🎉", "★")
+                    );
+                    println!("\n{FENISH_LINE}\n");
+                }
+                Ok(WatchStatus::Unfinished) => {
+                    println!("We hope you're enjoying learning about Rust!");
+                    println!("If you want to continue working on the exercises at a later point, you can simply run `rustlings watch` again");
+                }
+            }
+        }
+    }
+}
+
+// Compare, sign or verify grading reports
+fn report(subargs: ReportArgs) {
+    let key = || {
+        signing_key().unwrap_or_else(|| {
+            println!("No signing key given, set ${SIGNING_KEY_ENV}");
+            std::process::exit(1);
+        })
+    };
+    match subargs.nested {
+        ReportSubcommands::Diff(diffargs) => {
+            let load = |path: &str| {
+                ExerciseCheckList::load(path).unwrap_or_else(|e| {
+                    println!("Failed to read the report {path}: {e}");
+                    std::process::exit(1);
+                })
+            };
+            let (old, new) = (load(&diffargs.old), load(&diffargs.new));
+            let diff = ReportDiff::new(&old, &new, diffargs.slowdown);
+            diff.print();
+            if diffargs.fail_on_regression && diff.has_regressions() {
                 std::process::exit(1);
             }
-            Ok(WatchStatus::Finished) => {
-                println!(
-                    "{emoji} All exercises completed! {emoji}",
-                    emoji = Emoji("🎉", "★")
-                );
-                println!("\n{FENISH_LINE}\n");
+        }
+        ReportSubcommands::Sign(signargs) => {
+            let key = key();
+            let mut report = ExerciseCheckList::load(&signargs.report).unwrap_or_else(|e| {
+                println!("Failed to read the report {}: {e}", signargs.report);
+                std::process::exit(1);
+            });
+            sign(&mut report, &key);
+            let output = signargs.output.as_deref().unwrap_or(&signargs.report);
+            if let Err(e) = write_report(&JsonWriter, &report, output) {
+                println!("Failed to write the report to {output}: {e}");
+                std::process::exit(1);
             }
-            Ok(WatchStatus::Unfinished) => {
-                println!("We hope you're enjoying learning about Rust!");
-                println!("If you want to continue working on the exercises at a later point, you can simply run `rustlings watch` again");
+        }
+        ReportSubcommands::Verify(verifyargs) => {
+            let key = key();
+            let report = ExerciseCheckList::load(&verifyargs.report).unwrap_or_else(|e| {
+                println!("Failed to read the report {}: {e}", verifyargs.report);
+                std::process::exit(1);
+            });
+            match sign::verify(&report, &key) {
+                Ok(()) => println!("{}: signature is valid", verifyargs.report),
+                Err(e) => {
+                    println!("{}: {e}", verifyargs.report);
+                    std::process::exit(1);
+                }
             }
-        },
+        }
+    }
+}
+
+// Aggregate a directory of grading reports into class-wide tables
+fn class_report(subargs: ClassReportArgs) {
+    let class_report = ClassReport::load(Path::new(&subargs.reports)).unwrap_or_else(|e| {
+        println!("{e}");
+        std::process::exit(1);
+    });
+    if let Err(e) = class_report.write(Path::new(&subargs.output)) {
+        println!("Failed to write the class report to {}: {e}", subargs.output);
+        std::process::exit(1);
+    }
+    println!(
+        "Aggregated {} reports into {}",
+        class_report.students.len(),
+        subargs.output
+    );
+    println!("Hardest exercises:");
+    for exercise in class_report.hardest() {
+        println!("  {}: {:.1} %", exercise.name, exercise.pass_rate() * 100.0);
     }
 }
 
+// Summarize a grading report and send it to the course platform
+fn submit_report(subargs: SubmitArgs) {
+    let env_var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    let report = ExerciseCheckList::load(&subargs.report).unwrap_or_else(|e| {
+        println!("Failed to read the report {}: {e}", subargs.report);
+        std::process::exit(1);
+    });
+    let course_id = subargs
+        .course_id
+        .or_else(|| env_var("RUSTLINGS_COURSE_ID").and_then(|id| id.trim().parse().ok()))
+        .unwrap_or_else(|| {
+            println!("No course given, pass one with --course-id or $RUSTLINGS_COURSE_ID");
+            std::process::exit(1);
+        });
+    let summary = Summary::new(&report, subargs.channel, course_id, subargs.ext, subargs.name)
+        .unwrap_or_else(|e| {
+            println!("{e}");
+            std::process::exit(1);
+        });
+    let payload = serde_json::to_string_pretty(&summary).unwrap();
+    if let Some(output) = &subargs.output {
+        if let Err(e) = fs::write(output, &payload) {
+            println!("Failed to write the summary to {output}: {e}");
+            std::process::exit(1);
+        }
+    }
+    if subargs.dry_run {
+        println!("{payload}");
+        std::process::exit(0);
+    }
+
+    let endpoint = subargs
+        .endpoint
+        .or_else(|| env_var("RUSTLINGS_SUBMIT_URL"))
+        .unwrap_or_else(|| {
+            println!("No endpoint given, pass one with --endpoint or $RUSTLINGS_SUBMIT_URL");
+            std::process::exit(1);
+        });
+    let token = subargs.token.or_else(|| env_var("RUSTLINGS_SUBMIT_TOKEN"));
+    match submit(&endpoint, token.as_deref(), &payload, subargs.retries) {
+        Ok(response) => println!(
+            "Submitted {} / {} for {}: {response}",
+            summary.score, summary.total_score, summary.name
+        ),
+        Err(e) => {
+            println!("Failed to submit to {endpoint}: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+// Check the info.toml settings of an exercise before grading it
+fn validate_exercise(exercise: &Exercise, seed: Option<&str>) -> Result<(), String> {
+    if let Some(Err(e)) = exercise.deadline.as_deref().map(parse_deadline) {
+        return Err(format!("{}: {e}", exercise.name));
+    }
+    match (seed, &exercise.template) {
+        (Some(seed), Some(_)) => {
+            render(exercise, seed).map_err(|e| e.to_string())?;
+        }
+        (None, Some(_)) => {
+            return Err(format!(
+                "{}: templated exercises are graded against the student's variant, pass their seed with --seed",
+                exercise.name
+            ));
+        }
+        _ => {}
+    }
+    if exercise.is_crate() && !exercise.mutants.is_empty() {
+        return Err(format!("{}: mutants only apply to single-file exercises", exercise.name));
+    }
+    if matches!(exercise.mode, Mode::Mutation) == exercise.mutants.is_empty() {
+        return Err(format!("{}: mutants are required in, and only apply to, mutation mode", exercise.name));
+    }
+    if let Some(mutant) = exercise.mutants.iter().find(|mutant| !mutant.is_file()) {
+        return Err(format!("{}: mutant {} doesn't exist", exercise.name, mutant.display()));
+    }
+    if exercise.expected_error.is_some() != matches!(exercise.mode, Mode::CompileFail) {
+        return Err(format!("{}: expected_error is required in, and only applies to, compile_fail mode", exercise.name));
+    }
+    if matches!(exercise.mode, Mode::Doctest) {
+        exercise.rustdoc_args().map_err(|e| format!("{}: {e}", exercise.name))?;
+    }
+    if exercise.expected_output.is_some() && exercise.expected_output_file.is_some() {
+        return Err(format!("{}: set either expected_output or expected_output_file, not both", exercise.name));
+    }
+    if let Some(expected) = exercise.expected_output() {
+        if !matches!(exercise.mode, Mode::Compile) {
+            return Err(format!("{}: expected output only applies to exercises in compile mode", exercise.name));
+        }
+        let valid = expected
+            .map_err(|e| e.to_string())
+            .and_then(|expected| golden::validate(&expected, exercise.output_match));
+        if let Err(e) = valid {
+            return Err(format!("{}: {e}", exercise.name));
+        }
+    }
+    Ok(())
+}
+
+// Print the error and exit. `process::exit` skips `Drop`, so the temporary
+// worktree or copy graded in is removed first.
+fn exit_with(message: &str, workspace: Option<Workspace>, original_dir: &Path) -> ! {
+    std::env::set_current_dir(original_dir).unwrap();
+    drop(workspace);
+    println!("{message}");
+    std::process::exit(1);
+}
+
 fn spawn_watch_shell(
     failed_exercise_hint: &Arc<Mutex<Option<String>>>,
     should_quit: Arc<AtomicBool>,
@@ -443,6 +1133,19 @@ fn find_exercise<'a>(name: &str, exercises: &'a [Exercise]) -> &'a Exercise {
     }
 }
 
+// The exercises picked by `selection`, exiting if there are none
+fn select_exercises<'a>(selection: &Selection, exercises: &'a [Exercise]) -> Vec<(usize, &'a Exercise)> {
+    let selected = selection.apply(exercises).unwrap_or_else(|e| {
+        println!("{e}");
+        std::process::exit(1)
+    });
+    if selected.is_empty() {
+        println!("No exercises match the selection!");
+        std::process::exit(1);
+    }
+    selected
+}
+
 enum WatchStatus {
     Finished,
     Unfinished,
@@ -481,31 +1184,31 @@ fn watch(
     loop {
         match rx.recv_timeout(Duration::from_secs(1)) {
             Ok(event) => match event {
-                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b) => {
-                    if b.extension() == Some(OsStr::new("rs")) && b.exists() {
-                        let filepath = b.as_path().canonicalize().unwrap();
-                        let pending_exercises = exercises
-                            .iter()
-                            .find(|e| filepath.ends_with(&e.path))
-                            .into_iter()
-                            .chain(
-                                exercises
-                                    .iter()
-                                    .filter(|e| !e.looks_done() && !filepath.ends_with(&e.path)),
-                            );
-                        let num_done = exercises.iter().filter(|e| e.looks_done()).count();
-                        clear_screen();
-                        match verify(
-                            pending_exercises,
-                            (num_done, exercises.len()),
-                            verbose,
-                            success_hints,
-                        ) {
-                            Ok(_) => return Ok(WatchStatus::Finished),
-                            Err(exercise) => {
-                                let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
-                                *failed_exercise_hint = Some(to_owned_hint(exercise));
-                            }
+                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b)
+                    if b.extension() == Some(OsStr::new("rs")) && b.exists() =>
+                {
+                    let filepath = b.as_path().canonicalize().unwrap();
+                    let pending_exercises = exercises
+                        .iter()
+                        .find(|e| e.owns(&filepath))
+                        .into_iter()
+                        .chain(
+                            exercises
+                                .iter()
+                                .filter(|e| !e.looks_done() && !e.owns(&filepath)),
+                        );
+                    let num_done = exercises.iter().filter(|e| e.looks_done()).count();
+                    clear_screen();
+                    match verify(
+                        pending_exercises,
+                        (num_done, exercises.len()),
+                        verbose,
+                        success_hints,
+                    ) {
+                        Ok(_) => return Ok(WatchStatus::Finished),
+                        Err(exercise) => {
+                            let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
+                            *failed_exercise_hint = Some(to_owned_hint(exercise));
                         }
                     }
                 }
@@ -525,7 +1228,7 @@ fn watch(
 
 fn rustc_exists() -> bool {
     Command::new("rustc")
-        .args(&["--version"])
+        .args(["--version"])
         .stdout(Stdio::null())
         .spawn()
         .and_then(|mut child| child.wait())
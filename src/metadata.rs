@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Version of the report layout. Bump it whenever a field is renamed,
+/// removed or changes meaning; reports written before versioning read as 0.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Environment variables consulted for the student's name, in order
+const STUDENT_ENV_VARS: [&str; 2] = ["RUSTLINGS_STUDENT", "GITHUB_ACTOR"];
+
+/// Where and with what a grading run happened
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RunMetadata {
+    pub rustlings_version: String,
+    /// output of `rustc -V`
+    pub rustc_version: String,
+    /// `<os>-<arch>`, e.g. `linux-x86_64`
+    pub platform: String,
+    /// commit of the graded checkout, if it is a git repository
+    pub git_commit: Option<String>,
+    /// seconds since the Unix epoch
+    pub started_at: u64,
+    pub finished_at: u64,
+}
+
+impl RunMetadata {
+    /// Describe a run of this checkout between the two timestamps
+    pub fn collect(rustlings_version: &str, started_at: u64, finished_at: u64) -> RunMetadata {
+        RunMetadata {
+            rustlings_version: rustlings_version.to_string(),
+            rustc_version: rustc_version().to_string(),
+            platform: format!("{}-{}", env::consts::OS, env::consts::ARCH),
+            git_commit: git(&["rev-parse", "HEAD"]),
+            started_at,
+            finished_at,
+        }
+    }
+}
+
+/// Who is being graded: the name from `--student`, the environment
+/// or `git config user.name`, and the email from `--student-email`.
+/// The email from `git config user.email` is only used along with the name
+/// from git config, as anyone else's name would be paired with, say, the CI
+/// bot's or the teacher's email.
+pub fn student_identity(name: Option<String>, email: Option<String>) -> (Option<String>, Option<String>) {
+    let name = name.or_else(|| {
+        STUDENT_ENV_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.trim().is_empty())
+    });
+    match name {
+        Some(name) => (Some(name), email),
+        None => (
+            git(&["config", "user.name"]),
+            email.or_else(|| git(&["config", "user.email"])),
+        ),
+    }
+}
+
+/// The output of `rustc -V`, or an empty string if rustc can't be run
+pub fn rustc_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        Command::new("rustc")
+            .arg("-V")
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_default()
+    })
+}
+
+// Trimmed stdout of a successful, non-empty git command
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
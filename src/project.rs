@@ -1,3 +1,4 @@
+use crate::exercise::Exercise;
 use glob::glob;
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -57,10 +58,19 @@ impl RustAnalyzerProject {
 
     /// Parse the exercises folder for .rs files, any matches will create
     /// a new `crate` in rust-project.json which allows rust-analyzer to
-    /// treat it like a normal binary
-    pub fn exercises_to_json(&mut self) -> Result<(), Box<dyn Error>> {
+    /// treat it like a normal binary. Submodule files of directory exercises
+    /// belong to the crate rooted at their exercise's `main.rs` or `lib.rs`.
+    pub fn exercises_to_json(&mut self, exercises: &[Exercise]) -> Result<(), Box<dyn Error>> {
+        let submodules: Vec<PathBuf> = exercises
+            .iter()
+            .filter(|exercise| exercise.is_crate())
+            .flat_map(|exercise| exercise.files().into_iter().skip(1))
+            .collect();
         for path in glob("./exercises/**/*")? {
-            self.path_to_json(path?)?;
+            let path = path?;
+            if !submodules.iter().any(|submodule| path.ends_with(submodule)) {
+                self.path_to_json(path)?;
+            }
         }
         Ok(())
     }
@@ -86,7 +96,7 @@ impl RustAnalyzerProject {
 
         println!("Determined toolchain: {}\n", &toolchain);
 
-        self.sysroot_src = (std::path::Path::new(&*toolchain)
+        self.sysroot_src = (std::path::Path::new(toolchain)
             .join("lib")
             .join("rustlib")
             .join("src")
@@ -0,0 +1,169 @@
+use crate::exercise::Outcome;
+use crate::metadata::RunMetadata;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+
+/// The grading report written by `cicvverify`.
+/// Exercises are listed in the order they appear in `info.toml`.
+#[derive(Deserialize, Serialize)]
+pub struct ExerciseCheckList {
+    /// layout version of the report, see `metadata::SCHEMA_VERSION`
+    #[serde(default)]
+    pub schema_version: u32,
+    pub exercises: Vec<ExerciseResult>,
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub user_email: Option<String>,
+    pub statistics: ExerciseStatistics,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RunMetadata>,
+    /// hex HMAC-SHA256 of the rest of the report, see `sign::sign`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// How a single exercise fared, and enough of its output to tell why
+#[derive(Deserialize, Serialize)]
+pub struct ExerciseResult {
+    pub name: String,
+    /// path of the exercise source, as written in `info.toml`
+    #[serde(default)]
+    pub path: String,
+    pub result: bool,
+    pub outcome: Outcome,
+    /// 1-based position of the exercise in `info.toml`
+    #[serde(default)]
+    pub position: usize,
+    /// wall-clock time spent compiling and running the exercise
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// captured output, stripped of colors and truncated
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+    #[serde(default)]
+    pub category: String,
+    /// what the exercise is worth, and how much of that was earned
+    #[serde(default)]
+    pub points: f64,
+    #[serde(default)]
+    pub score: f64,
+    /// passed and total `#[test]` functions, when the harness reported them.
+    /// Hidden tests are not included here but counted in `hidden_tests`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<TestCounts>,
+    /// passed and total grader-only tests from `cicvverify --hidden-tests`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_tests: Option<TestCounts>,
+    /// caught (as `passed`) and total mutants of a mutation exercise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutants: Option<TestCounts>,
+    /// protected regions that no longer match the course's fingerprints
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tampered: Vec<String>,
+    /// whether the `I AM NOT DONE` marker was still in the source
+    #[serde(default)]
+    pub not_done_marker: bool,
+    /// the deadline from `info.toml`, as written there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
+    /// when the graded version was committed, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<u64>,
+    /// submitted after the deadline; the score already includes the late penalty
+    #[serde(default)]
+    pub late: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub total: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct  ExerciseStatistics {
+    pub total_exercations: usize,
+    pub total_succeeds: usize,
+    pub total_failures: usize,
+    pub total_time: u32,
+    #[serde(default)]
+    pub total_score: f64,
+    #[serde(default)]
+    pub max_score: f64,
+    /// exercises submitted after their deadline
+    #[serde(default)]
+    pub total_late: usize,
+    /// subtotals per exercise category, keyed by category name
+    #[serde(default)]
+    pub categories: BTreeMap<String, CategoryStatistics>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+pub struct CategoryStatistics {
+    pub exercises: usize,
+    pub succeeds: usize,
+    pub score: f64,
+    pub max_score: f64,
+}
+
+impl ExerciseStatistics {
+    /// Tally up the results of a grading run that took `total_time` seconds
+    pub fn new(results: &[ExerciseResult], total_time: u32) -> ExerciseStatistics {
+        let total_succeeds = results.iter().filter(|r| r.result).count();
+        let mut categories: BTreeMap<String, CategoryStatistics> = BTreeMap::new();
+        for result in results {
+            let category = categories.entry(result.category.clone()).or_default();
+            category.exercises += 1;
+            category.succeeds += usize::from(result.result);
+            category.score += result.score;
+            category.max_score += result.points;
+        }
+        ExerciseStatistics {
+            total_exercations: results.len(),
+            total_succeeds,
+            total_failures: results.len() - total_succeeds,
+            total_time,
+            total_score: results.iter().map(|r| r.score).sum(),
+            max_score: results.iter().map(|r| r.points).sum(),
+            total_late: results.iter().filter(|r| r.late).count(),
+            categories,
+        }
+    }
+
+    /// The score of the run. Reports without weighted points count one point per success.
+    pub fn score(&self) -> f64 {
+        if self.max_score == 0.0 {
+            self.total_succeeds as f64
+        } else {
+            self.total_score
+        }
+    }
+}
+
+impl ExerciseCheckList {
+    /// Read a report, including ones written before exercises had an `outcome`
+    pub fn load(path: &str) -> Result<ExerciseCheckList, Box<dyn Error>> {
+        let mut report: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let exercises = report
+            .get_mut("exercises")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object_mut);
+        for exercise in exercises {
+            if exercise.contains_key("outcome") {
+                continue;
+            }
+            // Older reports only say whether the exercise passed, not why it failed
+            let passed = exercise.get("result").and_then(Value::as_bool) == Some(true);
+            let outcome = if passed { Outcome::Passed } else { Outcome::Unknown };
+            exercise.insert("outcome".to_string(), serde_json::to_value(outcome)?);
+        }
+        Ok(serde_json::from_value(report)?)
+    }
+}
@@ -1,25 +1,29 @@
 
 use std::process::Command;
 
-use crate::exercise::{Exercise, Mode};
-use crate::verify::test;
+use crate::exercise::{Exercise, Mode, Outcome};
+use crate::verify::{test, warn_run_failure};
 use indicatif::ProgressBar;
 
 // Invoke the rust compiler on the path of the given exercise,
 // and run the ensuing binary.
 // The verbose argument helps determine whether or not to show
 // the output from the test harnesses (if the mode of the exercise is test)
-pub fn run(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
+pub fn run(exercise: &Exercise, verbose: bool) -> Result<(), Outcome> {
     match exercise.mode {
         Mode::Test => test(exercise, verbose)?,
         Mode::Compile => compile_and_run(exercise)?,
         Mode::Clippy => compile_and_run(exercise)?,
         Mode::BuildScript => test(exercise, verbose)?,
+        Mode::Mutation => test(exercise, verbose)?,
+        Mode::CompileFail => test(exercise, verbose)?,
+        Mode::Doctest => test(exercise, verbose)?,
     }
     Ok(())
 }
 
-// Resets the exercise by stashing the changes.
+// Resets the exercise by stashing the changes to its file,
+// or to every tracked file below it for a directory exercise.
 pub fn reset(exercise: &Exercise) -> Result<(), ()> {
     let command = Command::new("git")
         .args(["stash", "--"])
@@ -35,7 +39,7 @@ pub fn reset(exercise: &Exercise) -> Result<(), ()> {
 // Invoke the rust compiler on the path of the given exercise
 // and run the ensuing binary.
 // This is strictly for non-test binaries, so output is displayed
-fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
+fn compile_and_run(exercise: &Exercise) -> Result<(), Outcome> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Compiling {exercise}..."));
     progress_bar.enable_steady_tick(100);
@@ -50,7 +54,7 @@ fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
                 exercise
             );
             println!("{}", output.stderr);
-            return Err(());
+            return Err(output.outcome);
         }
     };
 
@@ -68,8 +72,8 @@ fn compile_and_run(exercise: &Exercise) -> Result<(), ()> {
             println!("{}", output.stdout);
             println!("{}", output.stderr);
 
-            warn!("Ran {} with errors", exercise);
-            Err(())
+            warn_run_failure(exercise, &output);
+            Err(output.outcome)
         }
     }
 }
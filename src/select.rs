@@ -0,0 +1,79 @@
+use crate::exercise::Exercise;
+use crate::report::ExerciseCheckList;
+use glob::Pattern;
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::Path;
+
+/// Which exercises `verify` and `cicvverify` should look at.
+/// An empty selection picks every exercise; otherwise an exercise
+/// must satisfy every kind of criterion that was given.
+#[derive(Default)]
+pub struct Selection {
+    /// glob patterns matched against exercise names, e.g. `algorithm*`
+    pub names: Vec<String>,
+    /// directories the exercise source must be in, e.g. `exercises/structs`
+    pub dirs: Vec<String>,
+    pub categories: Vec<String>,
+    /// first and last exercise of a range, in `info.toml` order
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// a previous `cicvverify` report; only exercises that failed in it are picked
+    pub failed_in: Option<String>,
+}
+
+impl Selection {
+    /// The selected exercises, with their 0-based index in `exercises`
+    pub fn apply<'a>(
+        &self,
+        exercises: &'a [Exercise],
+    ) -> Result<Vec<(usize, &'a Exercise)>, Box<dyn Error>> {
+        let names = self
+            .names
+            .iter()
+            .map(|name| Pattern::new(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let position = |name: &Option<String>, default: usize| match name {
+            Some(name) => exercises
+                .iter()
+                .position(|e| &e.name == name)
+                .ok_or_else(|| format!("No exercise found for '{name}'!")),
+            None => Ok(default),
+        };
+        let from = position(&self.from, 0)?;
+        let to = position(&self.to, exercises.len().saturating_sub(1))?;
+        let failed: Option<HashSet<String>> = match &self.failed_in {
+            Some(path) => Some(
+                ExerciseCheckList::load(path)
+                    .map_err(|e| format!("Failed to read the report {path}: {e}"))?
+                    .exercises
+                    .into_iter()
+                    .filter(|result| !result.result)
+                    .map(|result| result.name)
+                    .collect(),
+            ),
+            None => None,
+        };
+
+        let selected = exercises
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| (from..=to).contains(index))
+            .filter(|(_, e)| names.is_empty() || names.iter().any(|p| p.matches(&e.name)))
+            .filter(|(_, e)| {
+                self.dirs.is_empty()
+                    || self
+                        .dirs
+                        .iter()
+                        .any(|dir| e.path.starts_with(Path::new(dir)))
+            })
+            .filter(|(_, e)| self.categories.is_empty() || self.categories.contains(&e.category()))
+            .filter(|(_, e)| {
+                failed
+                    .as_ref()
+                    .is_none_or(|failed| failed.contains(&e.name))
+            })
+            .collect();
+        Ok(selected)
+    }
+}
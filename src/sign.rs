@@ -0,0 +1,64 @@
+use crate::report::ExerciseCheckList;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env;
+
+/// Environment variable holding the HMAC key. Only CI should have it,
+/// so that students can't produce a valid signature for an edited report.
+pub const SIGNING_KEY_ENV: &str = "RUSTLINGS_SIGNING_KEY";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The signing key from the environment, if there is one. Grading runs
+/// student code, which can read the environment of every process around it
+/// (e.g. through /proc), so the key must only be set where reports are
+/// signed or verified, never while `cicvverify` runs.
+pub fn signing_key() -> Option<Vec<u8>> {
+    env::var(SIGNING_KEY_ENV)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(String::into_bytes)
+}
+
+/// Sign everything in the report except the signature itself
+pub fn sign(report: &mut ExerciseCheckList, key: &[u8]) {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&canonical(report));
+    report.signature = Some(
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    );
+}
+
+/// Check that the report was signed with `key` and hasn't changed since
+pub fn verify(report: &ExerciseCheckList, key: &[u8]) -> Result<(), String> {
+    let signature = report.signature.as_deref().ok_or("the report is not signed")?;
+    let signature = decode_hex(signature).ok_or("the signature is not valid hex")?;
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&canonical(report));
+    mac.verify_slice(&signature)
+        .map_err(|_| "the signature does not match the report".to_string())
+}
+
+// The report as compact JSON with sorted keys and without its signature,
+// so that pretty-printing or reordering fields doesn't break the signature
+fn canonical(report: &ExerciseCheckList) -> Vec<u8> {
+    let mut value = serde_json::to_value(report).expect("reports always serialize");
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("signature");
+    }
+    serde_json::to_vec(&value).expect("reports always serialize")
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,374 @@
+use crate::exercise::Exercise;
+use console::style;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+// Keywords survive tokenization as themselves; every other identifier
+// becomes the same token, so renaming variables doesn't hide a copy
+const KEYWORDS: [&str; 39] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "yield",
+];
+
+/// Tuning for the winnowing algorithm and the report
+pub struct SimilarityOptions {
+    /// number of consecutive tokens hashed together
+    pub k: usize,
+    /// number of consecutive hashes one fingerprint is picked from
+    pub window: usize,
+    /// pairs at least this similar (0 to 1) are reported
+    pub threshold: f64,
+}
+
+/// Two submissions of the same exercise that look alike
+pub struct SimilarPair {
+    pub exercise: String,
+    pub first: PathBuf,
+    pub second: PathBuf,
+    /// shared fingerprints over all distinct fingerprints of the two submissions
+    pub similarity: f64,
+    /// per file of the exercise, relative to the submission: the inclusive,
+    /// 1-based line ranges covered by shared fingerprints
+    pub first_lines: Vec<(PathBuf, Vec<(usize, usize)>)>,
+    pub second_lines: Vec<(PathBuf, Vec<(usize, usize)>)>,
+}
+
+struct Token {
+    text: String,
+    line: usize,
+}
+
+// Fingerprints of one file: hash -> line ranges of the k-grams it was taken from
+type Fingerprints = HashMap<u64, Vec<(usize, usize)>>;
+
+// Fingerprints of every file of an exercise in one submission
+type Document = Vec<(PathBuf, Fingerprints)>;
+
+/// Compare every pair of submissions for each exercise. `base` is a checkout
+/// of the starter code; anything the submissions share with it is ignored.
+/// Exercises that are a directory are compared over all of their files.
+pub fn find_similar(
+    exercises: &[&Exercise],
+    submissions: &[PathBuf],
+    base: Option<&Path>,
+    options: &SimilarityOptions,
+) -> Vec<SimilarPair> {
+    let mut pairs = Vec::new();
+    for exercise in exercises {
+        let starter: HashSet<u64> = base
+            .and_then(|base| fingerprint_exercise(exercise, base, options))
+            .map(|document| hashes(&document))
+            .unwrap_or_default();
+        let documents: Vec<(&PathBuf, Document)> = submissions
+            .iter()
+            .filter_map(|submission| {
+                let mut document = fingerprint_exercise(exercise, submission, options)?;
+                for (_, fingerprints) in &mut document {
+                    fingerprints.retain(|hash, _| !starter.contains(hash));
+                }
+                Some((submission, document))
+            })
+            .collect();
+        for (i, (first, first_document)) in documents.iter().enumerate() {
+            let first_prints = hashes(first_document);
+            for (second, second_document) in &documents[i + 1..] {
+                let second_prints = hashes(second_document);
+                let shared: Vec<u64> = first_prints
+                    .intersection(&second_prints)
+                    .copied()
+                    .collect();
+                let distinct = first_prints.len() + second_prints.len() - shared.len();
+                if distinct == 0 {
+                    continue;
+                }
+                let similarity = shared.len() as f64 / distinct as f64;
+                if similarity >= options.threshold {
+                    pairs.push(SimilarPair {
+                        exercise: exercise.name.clone(),
+                        first: first.to_path_buf(),
+                        second: second.to_path_buf(),
+                        similarity,
+                        first_lines: matched_lines(first_document, &shared),
+                        second_lines: matched_lines(second_document, &shared),
+                    });
+                }
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    pairs
+}
+
+impl SimilarPair {
+    /// Print the pair and the line ranges they share. With `show_matches`,
+    /// also print those lines of both files, highlighted.
+    pub fn print(&self, show_matches: bool) {
+        println!(
+            "{}: {} <-> {} {}",
+            self.exercise,
+            self.first.display(),
+            self.second.display(),
+            style(format!("{:.1} %", self.similarity * 100.0)).red()
+        );
+        for (submission, files) in [
+            (&self.first, &self.first_lines),
+            (&self.second, &self.second_lines),
+        ] {
+            for (file, lines) in files {
+                let path = submission.join(file);
+                let ranges: Vec<String> = lines
+                    .iter()
+                    .map(|(start, end)| format!("{start}-{end}"))
+                    .collect();
+                println!("  {}: lines {}", path.display(), ranges.join(", "));
+                if show_matches {
+                    let source = fs::read_to_string(&path).unwrap_or_default();
+                    for (number, line) in source.lines().enumerate() {
+                        let number = number + 1;
+                        if lines
+                            .iter()
+                            .any(|(start, end)| (*start..=*end).contains(&number))
+                        {
+                            println!("    {:>4} | {}", number, style(line).yellow());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Fingerprint the files of the exercise as found under `dir`,
+// or None if the submission has none of them
+fn fingerprint_exercise(
+    exercise: &Exercise,
+    dir: &Path,
+    options: &SimilarityOptions,
+) -> Option<Document> {
+    let document: Document = exercise
+        .files_in(dir)
+        .into_iter()
+        .filter_map(|file| {
+            let fingerprints = fingerprint_file(&dir.join(&file), options)?;
+            Some((file, fingerprints))
+        })
+        .collect();
+    (!document.is_empty()).then_some(document)
+}
+
+fn fingerprint_file(path: &Path, options: &SimilarityOptions) -> Option<Fingerprints> {
+    let source = fs::read_to_string(path).ok()?;
+    Some(winnow(&tokenize(&source), options.k, options.window))
+}
+
+// Every fingerprint of a document, whichever file it came from
+fn hashes(document: &Document) -> HashSet<u64> {
+    document
+        .iter()
+        .flat_map(|(_, fingerprints)| fingerprints.keys().copied())
+        .collect()
+}
+
+// For each file with shared fingerprints, merge their line ranges into as few ranges as possible
+fn matched_lines(document: &Document, shared: &[u64]) -> Vec<(PathBuf, Vec<(usize, usize)>)> {
+    document
+        .iter()
+        .filter_map(|(file, fingerprints)| {
+            let mut ranges: Vec<(usize, usize)> = shared
+                .iter()
+                .filter_map(|hash| fingerprints.get(hash))
+                .flatten()
+                .copied()
+                .collect();
+            ranges.sort();
+            let mut merged: Vec<(usize, usize)> = Vec::new();
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            (!merged.is_empty()).then(|| (file.clone(), merged))
+        })
+        .collect()
+}
+
+// Hash every k-gram of tokens and keep the smallest hash of each window
+// (the rightmost one on ties), as described in "Winnowing: Local Algorithms
+// for Document Fingerprinting" by Schleimer, Wilkerson and Aiken
+fn winnow(tokens: &[Token], k: usize, window: usize) -> Fingerprints {
+    let k = k.max(1);
+    let hashes: Vec<(u64, usize, usize)> = tokens
+        .windows(k)
+        .map(|gram| {
+            let mut hasher = DefaultHasher::new();
+            for token in gram {
+                token.text.hash(&mut hasher);
+            }
+            (hasher.finish(), gram[0].line, gram[k - 1].line)
+        })
+        .collect();
+    let mut fingerprints = Fingerprints::new();
+    let mut record = |(hash, start, end): (u64, usize, usize)| {
+        let ranges = fingerprints.entry(hash).or_default();
+        if !ranges.contains(&(start, end)) {
+            ranges.push((start, end));
+        }
+    };
+    if hashes.len() <= window {
+        // Too short to winnow: keep every hash
+        hashes.iter().copied().for_each(&mut record);
+        return fingerprints;
+    }
+    for hashes in hashes.windows(window.max(1)) {
+        let smallest = hashes
+            .iter()
+            .rev()
+            .min_by_key(|(hash, _, _)| *hash)
+            .copied()
+            .expect("windows are never empty");
+        record(smallest);
+    }
+    fingerprints
+}
+
+// Split Rust source into tokens, dropping whitespace and comments.
+// Identifiers, numbers, strings and chars are normalized, so only
+// the structure of the code is compared.
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let count_lines = |text: &[char]| text.iter().filter(|&&c| c == '\n').count();
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let start_line = line;
+        let text = if c.is_whitespace() {
+            i += 1;
+            None
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            None
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            // Block comments nest in Rust
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            None
+        } else if let Some(end) = raw_string_end(&chars, i) {
+            i = end;
+            Some("STR".to_string())
+        } else if c == '"' || (c == 'b' && chars.get(i + 1) == Some(&'"')) {
+            i += if c == 'b' { 2 } else { 1 };
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i += 1;
+            Some("STR".to_string())
+        } else if c == '\'' {
+            // A char literal like 'a' or '\n', or a lifetime like 'a
+            if chars.get(i + 1) == Some(&'\\') {
+                i += 2;
+                while i < chars.len() && chars[i] != '\'' {
+                    i += 1;
+                }
+                i += 1;
+                Some("CHAR".to_string())
+            } else if chars.get(i + 2) == Some(&'\'') {
+                i += 3;
+                Some("CHAR".to_string())
+            } else {
+                i += 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                Some("LIFETIME".to_string())
+            }
+        } else if c.is_ascii_digit() {
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                // Don't swallow the `..` of a range like `0..n`
+                if chars[i] == '.' && chars.get(i + 1) == Some(&'.') {
+                    break;
+                }
+                i += 1;
+            }
+            Some("NUM".to_string())
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            Some(if KEYWORDS.contains(&word.as_str()) {
+                word
+            } else {
+                "ID".to_string()
+            })
+        } else {
+            i += 1;
+            Some(c.to_string())
+        };
+        line += count_lines(&chars[start..i.min(chars.len())]);
+        if let Some(text) = text {
+            tokens.push(Token {
+                text,
+                line: start_line,
+            });
+        }
+    }
+    tokens
+}
+
+// If a raw string like r"..." or br#"..."# starts at `start`, the index just past its end
+fn raw_string_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start;
+    if chars.get(i) == Some(&'b') {
+        i += 1;
+    }
+    if chars.get(i) != Some(&'r') {
+        return None;
+    }
+    i += 1;
+    let hashes = chars[i..].iter().take_while(|&&c| c == '#').count();
+    i += hashes;
+    if chars.get(i) != Some(&'"') {
+        return None;
+    }
+    i += 1;
+    while i < chars.len() {
+        if chars[i] == '"'
+            && chars[i + 1..]
+                .iter()
+                .take(hashes)
+                .filter(|&&c| c == '#')
+                .count()
+                == hashes
+        {
+            return Some(i + 1 + hashes);
+        }
+        i += 1;
+    }
+    Some(chars.len())
+}
@@ -0,0 +1,100 @@
+use crate::report::ExerciseCheckList;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+// Delay before the first retry, doubled after every further failure
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// The course summary the classroom backend expects
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    pub channel: String,
+    pub course_id: i64,
+    pub ext: String,
+    pub name: String,
+    /// exercises passed; the backend has always been sent whole-number counts here
+    pub score: usize,
+    /// exercises graded
+    pub total_score: usize,
+    /// the score with weighted points and partial credit from info.toml
+    pub weighted_score: f64,
+    pub max_weighted_score: f64,
+    /// the whole report, if it is signed, so the backend can check the signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_report: Option<Value>,
+}
+
+impl Summary {
+    /// Summarize a grading report for `name`, or for the student recorded in the report
+    pub fn new(
+        report: &ExerciseCheckList,
+        channel: String,
+        course_id: i64,
+        ext: String,
+        name: Option<String>,
+    ) -> Result<Summary, String> {
+        let name = name
+            .or_else(|| report.user_name.clone())
+            .ok_or("The report doesn't name a student, pass one with --name")?;
+        let statistics = &report.statistics;
+        let max_weighted_score = if statistics.max_score == 0.0 {
+            statistics.total_exercations as f64
+        } else {
+            statistics.max_score
+        };
+        Ok(Summary {
+            channel,
+            course_id,
+            ext,
+            name,
+            score: statistics.total_succeeds,
+            total_score: statistics.total_exercations,
+            weighted_score: statistics.score(),
+            max_weighted_score,
+            signed_report: report
+                .signature
+                .is_some()
+                .then(|| serde_json::to_value(report).expect("reports always serialize")),
+        })
+    }
+}
+
+/// POST `payload` as JSON to `endpoint`, retrying up to `retries` times
+/// on connection errors and server errors. Client errors (4xx other than 429)
+/// are not retried, since sending the same payload again won't help.
+/// Returns the body of the response.
+pub fn submit(
+    endpoint: &str,
+    token: Option<&str>,
+    payload: &str,
+    retries: u32,
+) -> Result<String, Box<dyn Error>> {
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 0;
+    loop {
+        let mut request = ureq::post(endpoint)
+            .set("accept", "application/json;charset=utf-8")
+            .set("Content-Type", "application/json");
+        if let Some(token) = token {
+            request = request.set("token", token);
+        }
+        let error = match request.send_string(payload) {
+            Ok(response) => return Ok(response.into_string()?),
+            Err(ureq::Error::Status(status, _)) if status < 500 && status != 429 => {
+                return Err(format!("{endpoint} rejected the submission with status {status}").into());
+            }
+            Err(error) => error,
+        };
+        if attempt == retries {
+            return Err(error.into());
+        }
+        attempt += 1;
+        println!("Submission failed ({error}), retrying in {} ms", delay.as_millis());
+        thread::sleep(delay);
+        delay *= 2;
+    }
+}
@@ -0,0 +1,220 @@
+use crate::exercise::{Exercise, Mode};
+use crate::hidden::read_location;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+
+/// Canonical fingerprints of the course's starter code:
+/// exercise name -> protected region name -> SHA-256 of the region
+pub type Fingerprints = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Where `rustlings fingerprint` writes fingerprints by default
+pub const DEFAULT_FINGERPRINTS_PATH: &str = "fingerprints.toml";
+
+/// Where `cicvverify` reads fingerprints from when not given `--fingerprints`
+pub const FINGERPRINTS_ENV: &str = "RUSTLINGS_FINGERPRINTS";
+
+// Markers for protecting an arbitrary region of an exercise
+const PROTECTED_START: &str = "// @protected";
+const PROTECTED_END: &str = "// @end-protected";
+
+/// What `cicvverify` does with an exercise whose protected regions were changed
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TamperPolicy {
+    /// record the changed regions in the report, but grade as usual
+    Flag,
+    /// record the changed regions and fail the exercise
+    Fail,
+}
+
+impl FromStr for TamperPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flag" => Ok(TamperPolicy::Flag),
+            "fail" => Ok(TamperPolicy::Fail),
+            _ => Err(format!(
+                "unknown tamper policy `{s}`, expected one of: flag, fail"
+            )),
+        }
+    }
+}
+
+/// Fingerprint the protected regions of every exercise in its current state.
+/// Run this on the course's starter code, never on a student's solutions.
+pub fn fingerprint_exercises(exercises: &[Exercise]) -> Fingerprints {
+    exercises
+        .iter()
+        .map(|exercise| {
+            (exercise.name.clone(), exercise_fingerprints(exercise, &exercise.source()))
+        })
+        .filter(|(_, regions): &(String, BTreeMap<String, String>)| !regions.is_empty())
+        .collect()
+}
+
+/// Fingerprints of the protected regions of `source`, keyed by region name
+pub fn region_fingerprints(source: &str) -> BTreeMap<String, String> {
+    protected_regions(source)
+        .into_iter()
+        .map(|(name, text)| (name, fingerprint(&text)))
+        .collect()
+}
+
+/// Fingerprints of the protected regions of `source`, a version of `exercise`.
+/// The test modules of exercises whose tests the student writes are left out.
+pub fn exercise_fingerprints(exercise: &Exercise, source: &str) -> BTreeMap<String, String> {
+    let mut regions = region_fingerprints(source);
+    if exercise.editable_tests || matches!(exercise.mode, Mode::Mutation) {
+        regions.retain(|name, _| !name.starts_with("mod "));
+    }
+    regions
+}
+
+/// Read fingerprints from a file or an http(s) URL
+pub fn load_fingerprints(source: &str) -> Result<Fingerprints, Box<dyn Error>> {
+    Ok(toml::from_str(&read_location(source)?)?)
+}
+
+pub fn save_fingerprints(path: &str, fingerprints: &Fingerprints) -> Result<(), Box<dyn Error>> {
+    fs::write(path, toml::to_string(fingerprints)?)?;
+    Ok(())
+}
+
+/// Names of the protected regions of `exercise` that no longer match
+/// their canonical fingerprint, including regions that were removed,
+/// and test modules and regions the fingerprints don't have at all
+pub fn changed_regions(exercise: &Exercise, canonical: &BTreeMap<String, String>) -> Vec<String> {
+    let current = exercise_fingerprints(exercise, &exercise.source());
+    let changed = canonical
+        .iter()
+        .filter(|(name, hash)| current.get(*name) != Some(*hash))
+        .map(|(name, _)| name.clone());
+    let added = current
+        .keys()
+        .filter(|name| !canonical.contains_key(*name))
+        .cloned();
+    changed.chain(added).collect()
+}
+
+/// The `#[cfg(test)]` modules of `source`, attribute included, with every
+/// other line left empty, so the modules stay on their original lines
+pub fn test_modules_in_place(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut kept = vec![""; lines.len()];
+    let mut index = 0;
+    while index < lines.len() {
+        if lines[index].trim() == "#[cfg(test)]" {
+            if let Some((_, end)) = test_module(&lines, index) {
+                kept[index..=end].copy_from_slice(&lines[index..=end]);
+                index = end;
+            }
+        }
+        index += 1;
+    }
+    kept.join("\n")
+}
+
+// SHA-256 of the region with all whitespace collapsed,
+// so that reformatting the file doesn't count as tampering
+fn fingerprint(text: &str) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+// Find the `#[cfg(test)]` modules and the regions between
+// `// @protected <name>` and `// @end-protected` markers
+fn protected_regions(source: &str) -> Vec<(String, String)> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut regions = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index].trim();
+        if line == "#[cfg(test)]" {
+            if let Some((name, end)) = test_module(&lines, index) {
+                regions.push((format!("mod {name}"), lines[index..=end].join("\n")));
+                index = end;
+            }
+        } else if let Some(name) = line.strip_prefix(PROTECTED_START) {
+            let name = name.trim();
+            if let Some(end) = (index + 1..lines.len()).find(|&i| lines[i].trim() == PROTECTED_END) {
+                let name = if name.is_empty() { format!("line {}", index + 1) } else { name.to_string() };
+                regions.push((name, lines[index + 1..end].join("\n")));
+                index = end;
+            }
+        }
+        index += 1;
+    }
+    regions
+}
+
+// The name of the module following the `#[cfg(test)]` attribute on line `start`,
+// and the line its closing brace is on
+fn test_module(lines: &[&str], start: usize) -> Option<(String, usize)> {
+    let declaration = lines.get(start + 1)?.trim();
+    if declaration.ends_with(';') {
+        return None; // `mod tests;` lives in another file
+    }
+    let name = declaration
+        .strip_prefix("pub ")
+        .unwrap_or(declaration)
+        .strip_prefix("mod ")?
+        .trim_end_matches('{')
+        .trim()
+        .to_string();
+    let mut depth = 0;
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        depth += brace_delta(line);
+        if depth <= 0 && line.contains('}') {
+            return Some((name, index));
+        }
+    }
+    None
+}
+
+// Net number of opened braces on a line, ignoring braces
+// inside string and char literals and after `//` comments
+fn brace_delta(line: &str) -> i32 {
+    let mut delta = 0;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => delta += 1,
+            '}' => delta -= 1,
+            '/' if chars.peek() == Some(&'/') => break,
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '\'' => {
+                // Skip char literals like '{' and '\'', but not lifetimes like 'a
+                let mut lookahead = chars.clone();
+                let literal_len = match lookahead.next() {
+                    Some('\\') => {
+                        lookahead.next();
+                        lookahead.position(|c| c == '\'').map(|n| n + 3)
+                    }
+                    Some(_) if lookahead.next() == Some('\'') => Some(2),
+                    _ => None,
+                };
+                for _ in 0..literal_len.unwrap_or(0) {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    delta
+}
@@ -0,0 +1,98 @@
+use crate::exercise::Exercise;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+
+/// Where `rustlings init --seed` records the student's seed,
+/// so that grading can re-derive the same variants
+pub const SEED_FILE: &str = ".rustlings-seed";
+
+/// The values a template placeholder can take
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Param {
+    /// an integer from `range[0]` to `range[1]`, inclusive
+    Range { range: [i64; 2] },
+    /// one of the given strings, e.g. a function name
+    Choices { choices: Vec<String> },
+}
+
+/// Instantiate the template of `exercise` for `seed`: every `{{name}}` of a
+/// declared parameter is replaced by the value the seed picks for it.
+/// The same seed always gives the same variant.
+pub fn render(exercise: &Exercise, seed: &str) -> Result<String, Box<dyn Error>> {
+    let template = exercise
+        .template
+        .as_ref()
+        .ok_or_else(|| format!("{} is not a template", exercise.name))?;
+    let mut source = fs::read_to_string(template)
+        .map_err(|e| format!("Failed to read the template {}: {e}", template.display()))?;
+    for (name, param) in &exercise.params {
+        let value = match param {
+            Param::Range { range: [low, high] } if low <= high => {
+                in_range(*low, *high, pick(seed, &exercise.name, name)).to_string()
+            }
+            Param::Choices { choices } if !choices.is_empty() => {
+                let index = pick(seed, &exercise.name, name) % choices.len() as u64;
+                choices[index as usize].clone()
+            }
+            _ => return Err(format!("{}: parameter `{name}` has no values", exercise.name).into()),
+        };
+        source = source.replace(&format!("{{{{{name}}}}}"), &value);
+    }
+    Ok(source)
+}
+
+pub fn load_seed() -> Option<String> {
+    fs::read_to_string(SEED_FILE)
+        .ok()
+        .map(|seed| seed.trim().to_string())
+        .filter(|seed| !seed.is_empty())
+}
+
+pub fn save_seed(seed: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(SEED_FILE, format!("{seed}\n"))?;
+    Ok(())
+}
+
+// Map `value` onto `low..=high`. The full i64 range has one value more than
+// fits in a u64 span, so there every value already is a valid offset.
+fn in_range(low: i64, high: i64, value: u64) -> i64 {
+    let offset = match high.abs_diff(low).checked_add(1) {
+        Some(span) => value % span,
+        None => value,
+    };
+    low.wrapping_add(offset as i64)
+}
+
+// A number derived from the seed, exercise and parameter alone. SHA-256 rather
+// than std's hasher, whose output may change between Rust versions.
+fn pick(seed: &str, exercise: &str, param: &str) -> u64 {
+    let mut hasher = Sha256::new();
+    for part in [seed, exercise, param] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    let digest = hasher.finalize();
+    u64::from_le_bytes(digest[..8].try_into().expect("SHA-256 digests are 32 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_range_stays_within_the_bounds() {
+        assert_eq!(in_range(2, 4, 0), 2);
+        assert_eq!(in_range(2, 4, 5), 4);
+        assert_eq!(in_range(-3, -3, u64::MAX), -3);
+    }
+
+    #[test]
+    fn in_range_covers_the_full_i64_range() {
+        assert_eq!(in_range(i64::MIN, i64::MAX, 0), i64::MIN);
+        assert_eq!(in_range(i64::MIN, i64::MAX, u64::MAX), i64::MAX);
+        assert_eq!(in_range(i64::MIN + 1, i64::MAX, u64::MAX - 1), i64::MAX);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::exercise::{CompiledExercise, Exercise, Mode, State};
+use crate::exercise::{CompiledExercise, Exercise, ExerciseOutput, Mode, Outcome, State};
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::env;
@@ -30,6 +30,9 @@ pub fn verify<'a>(
             Mode::Compile => compile_and_run_interactively(exercise, success_hints),
             Mode::Clippy => compile_only(exercise, success_hints),
             Mode::BuildScript => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
+            Mode::Mutation => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
+            Mode::CompileFail => compile_only(exercise, success_hints),
+            Mode::Doctest => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
 
         };
         if !compile_result.unwrap_or(false) {
@@ -48,13 +51,13 @@ enum RunMode {
 }
 
 // Compile and run the resulting test harness of the given Exercise
-pub fn test(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
+pub fn test(exercise: &Exercise, verbose: bool) -> Result<(), Outcome> {
     compile_and_test(exercise, RunMode::NonInteractive, verbose, false)?;
     Ok(())
 }
 
 // Invoke the rust compiler without running the resulting binary
-fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
+fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, Outcome> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Compiling {exercise}..."));
     progress_bar.enable_steady_tick(100);
@@ -66,7 +69,7 @@ fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
 }
 
 // Compile the given Exercise and run the resulting binary in an interactive mode
-fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
+fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Result<bool, Outcome> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Compiling {exercise}..."));
     progress_bar.enable_steady_tick(100);
@@ -80,10 +83,10 @@ fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Re
     let output = match result {
         Ok(output) => output,
         Err(output) => {
-            warn!("Ran {} with errors", exercise);
+            warn_run_failure(exercise, &output);
             println!("{}", output.stdout);
             println!("{}", output.stderr);
-            return Err(());
+            return Err(output.outcome);
         }
     };
 
@@ -92,7 +95,7 @@ fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Re
 
 // Compile the given Exercise as a test harness and display
 // the output if verbose is set to true
-fn compile_and_test(exercise: &Exercise, run_mode: RunMode, verbose: bool, success_hints: bool) -> Result<bool, ()> {
+fn compile_and_test(exercise: &Exercise, run_mode: RunMode, verbose: bool, success_hints: bool) -> Result<bool, Outcome> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.set_message(format!("Testing {exercise}..."));
     progress_bar.enable_steady_tick(100);
@@ -113,38 +116,57 @@ fn compile_and_test(exercise: &Exercise, run_mode: RunMode, verbose: bool, succe
             }
         }
         Err(output) => {
-            warn!(
-                "Testing of {} failed! Please try again. Here's the output:",
-                exercise
-            );
+            match exercise.limit_message(output.outcome) {
+                Some(message) => warn!("{}", message),
+                None => warn!(
+                    "Testing of {} failed! Please try again. Here's the output:",
+                    exercise
+                ),
+            }
             println!("{}", output.stdout);
-            Err(())
+            Err(output.outcome)
         }
     }
 }
 
 // Compile the given Exercise and return an object with information
 // about the state of the compilation
-fn compile<'a, 'b>(
+fn compile<'a>(
     exercise: &'a Exercise,
-    progress_bar: &'b ProgressBar,
-) -> Result<CompiledExercise<'a>, ()> {
+    progress_bar: &ProgressBar,
+) -> Result<CompiledExercise<'a>, Outcome> {
     let compilation_result = exercise.compile();
 
     match compilation_result {
         Ok(compilation) => Ok(compilation),
         Err(output) => {
             progress_bar.finish_and_clear();
-            warn!(
-                "Compiling of {} failed! Please try again. Here's the output:",
-                exercise
-            );
+            match output.outcome {
+                Outcome::UnexpectedlyCompiled => warn!("{} was expected not to compile:", exercise),
+                _ => warn!(
+                    "Compiling of {} failed! Please try again. Here's the output:",
+                    exercise
+                ),
+            }
             println!("{}", output.stderr);
-            Err(())
+            Err(output.outcome)
         }
     }
 }
 
+// Report why running the given Exercise failed, calling out
+// runs that were killed for exceeding their time or memory limit
+// and runs that printed something other than the expected output
+pub fn warn_run_failure(exercise: &Exercise, output: &ExerciseOutput) {
+    match exercise.limit_message(output.outcome) {
+        Some(message) => warn!("{}", message),
+        None if output.outcome == Outcome::WrongOutput => {
+            warn!("{} ran, but its output is not what was expected", exercise)
+        }
+        None => warn!("Ran {} with errors", exercise),
+    }
+}
+
 fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, success_hints: bool) -> bool {
     let context = match exercise.state() {
         State::Done => return true,
@@ -155,6 +177,9 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
         Mode::Test => success!("Successfully tested {}!", exercise),
         Mode::Clippy => success!("Successfully compiled {}!", exercise),
         Mode::BuildScript => success!("Successfully compiled {}!", exercise),
+        Mode::Mutation => success!("Successfully tested {}!", exercise),
+        Mode::CompileFail => success!("{} fails to compile, as it should!", exercise),
+        Mode::Doctest => success!("Successfully tested {}!", exercise),
     }
 
     let no_emoji = env::var("NO_EMOJI").is_ok();
@@ -170,6 +195,9 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
         Mode::Test => "The code is compiling, and the tests pass!",
         Mode::Clippy => clippy_success_msg,
         Mode::BuildScript => "Build script works!",
+        Mode::Mutation => "The tests pass, and they catch every mutant!",
+        Mode::CompileFail => "The compiler rejects the code with the expected error!",
+        Mode::Doctest => "The code is compiling, and the doc examples pass!",
     };
     println!();
     if no_emoji {
@@ -195,9 +223,15 @@ fn prompt_for_completion(exercise: &Exercise, prompt_output: Option<String>, suc
     }
 
     println!("You can keep working on this exercise,");
+    // Directory exercises have several files, so say which one holds the marker
+    let location = match context.first() {
+        Some(context_line) if exercise.is_crate() => format!(" in {}", context_line.path.display()),
+        _ => String::new(),
+    };
     println!(
-        "or jump into the next one by removing the {} comment:",
-        style("`I AM NOT DONE`").bold()
+        "or jump into the next one by removing the {} comment{}:",
+        style("`I AM NOT DONE`").bold(),
+        location
     );
     println!();
     for context_line in context {
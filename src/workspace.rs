@@ -0,0 +1,94 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+// Directories that are never copied into a workspace:
+// build output and cached results only slow the copy down
+const SKIPPED_DIRS: [&str; 2] = ["target", ".rustlings-cache"];
+
+/// A private copy of a course checkout to grade, so that grading
+/// never touches the exercises in the current directory.
+/// The copy is removed when the workspace is dropped.
+pub struct Workspace {
+    dir: PathBuf,
+    /// the repository the workspace is a git worktree of, if it is one
+    repository: Option<PathBuf>,
+}
+
+impl Workspace {
+    /// Check out `rev` of the git repository at `repository` into a temporary worktree
+    pub fn checkout(repository: &Path, rev: &str) -> Result<Workspace, Box<dyn Error>> {
+        let repository = repository.canonicalize()?;
+        let dir = temporary_dir();
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&repository)
+            .args(["worktree", "add", "--detach"])
+            .arg(&dir)
+            .arg(rev)
+            .output()?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to check out {rev}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(Workspace {
+            dir,
+            repository: Some(repository),
+        })
+    }
+
+    /// Copy the checkout or extracted submission at `source` into a temporary directory
+    pub fn copy(source: &Path) -> Result<Workspace, Box<dyn Error>> {
+        let dir = temporary_dir();
+        let workspace = Workspace {
+            dir,
+            repository: None,
+        };
+        copy_dir(source, &workspace.dir)?;
+        Ok(workspace)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if let Some(repository) = &self.repository {
+            let _ = Command::new("git")
+                .arg("-C")
+                .arg(repository)
+                .args(["worktree", "remove", "--force"])
+                .arg(&self.dir)
+                .output();
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn temporary_dir() -> PathBuf {
+    env::temp_dir().join(format!("rustlings_workspace_{}", process::id()))
+}
+
+fn copy_dir(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped) {
+                copy_dir(&entry.path(), &target)?;
+            }
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use crate::report::{ExerciseCheckList, ExerciseResult};
+use regex::Regex;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The formats `cicvverify` can write its report in
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ReportFormat {
+    Json,
+    JUnit,
+    Tap,
+    Github,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ReportFormat::Json),
+            "junit" => Ok(ReportFormat::JUnit),
+            "tap" => Ok(ReportFormat::Tap),
+            "github" => Ok(ReportFormat::Github),
+            _ => Err(format!(
+                "unknown report format `{s}`, expected one of: json, junit, tap, github"
+            )),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// Where the report goes when no `--output` is given.
+    /// `-` stands for standard output.
+    pub fn default_output(self) -> &'static str {
+        match self {
+            ReportFormat::Json => ".github/result/check_result.json",
+            ReportFormat::JUnit => ".github/result/check_result.xml",
+            ReportFormat::Tap => ".github/result/check_result.tap",
+            ReportFormat::Github => "-",
+        }
+    }
+
+    pub fn writer(self) -> Box<dyn ReportWriter> {
+        match self {
+            ReportFormat::Json => Box::new(JsonWriter),
+            ReportFormat::JUnit => Box::new(JUnitWriter),
+            ReportFormat::Tap => Box::new(TapWriter),
+            ReportFormat::Github => Box::new(GithubWriter),
+        }
+    }
+}
+
+/// Turns a grading report into the text of one output format
+pub trait ReportWriter {
+    fn render(&self, report: &ExerciseCheckList) -> String;
+}
+
+/// Render the report with the given writer and write it to `output`,
+/// creating any missing parent directories. `-` writes to standard output.
+pub fn write_report(
+    writer: &dyn ReportWriter,
+    report: &ExerciseCheckList,
+    output: &str,
+) -> io::Result<()> {
+    let rendered = writer.render(report);
+    if output == "-" {
+        println!("{rendered}");
+        return Ok(());
+    }
+    if let Some(parent) = Path::new(output).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, rendered)
+}
+
+/// The `check_result.json` format consumed by the classroom workflow
+pub struct JsonWriter;
+
+impl ReportWriter for JsonWriter {
+    fn render(&self, report: &ExerciseCheckList) -> String {
+        serde_json::to_string_pretty(report).expect("Failed to serialize report")
+    }
+}
+
+/// JUnit XML, one test case per exercise
+pub struct JUnitWriter;
+
+impl ReportWriter for JUnitWriter {
+    fn render(&self, report: &ExerciseCheckList) -> String {
+        let tests = report.exercises.len();
+        let failures = report.exercises.iter().filter(|e| !e.result).count();
+        let time = report.statistics.total_time;
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"rustlings\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time}\">"
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"rustlings\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time}\">"
+        );
+        for exercise in &report.exercises {
+            let classname = Path::new(&exercise.path)
+                .parent()
+                .map(|dir| dir.display().to_string().replace(['/', '\\'], "."))
+                .unwrap_or_default();
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&exercise.name),
+                xml_escape(&classname),
+                exercise.duration_ms as f64 / 1000.0
+            );
+            if exercise.result {
+                xml.push_str(" />\n");
+                continue;
+            }
+            xml.push_str(">\n");
+            let _ = writeln!(
+                xml,
+                "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                xml_escape(&failure_message(exercise)),
+                exercise.outcome,
+                xml_escape(&exercise.stderr)
+            );
+            if !exercise.stdout.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "      <system-out>{}</system-out>",
+                    xml_escape(&exercise.stdout)
+                );
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// TAP version 13, with the test points numbered 1..N in report order
+pub struct TapWriter;
+
+impl ReportWriter for TapWriter {
+    fn render(&self, report: &ExerciseCheckList) -> String {
+        let mut tap = String::from("TAP version 13\n");
+        let _ = writeln!(tap, "1..{}", report.exercises.len());
+        for (index, exercise) in report.exercises.iter().enumerate() {
+            let status = if exercise.result { "ok" } else { "not ok" };
+            let _ = writeln!(tap, "{status} {} - {}", index + 1, exercise.name);
+            if !exercise.result {
+                tap.push_str("  ---\n");
+                let _ = writeln!(tap, "  outcome: {}", exercise.outcome);
+                let _ = writeln!(tap, "  message: {:?}", failure_message(exercise));
+                let _ = writeln!(tap, "  file: {}", exercise.path);
+                let _ = writeln!(tap, "  duration_ms: {}", exercise.duration_ms);
+                tap.push_str("  ...\n");
+            }
+        }
+        tap
+    }
+}
+
+/// GitHub Actions `::error` workflow commands that annotate the failing exercise files
+pub struct GithubWriter;
+
+impl ReportWriter for GithubWriter {
+    fn render(&self, report: &ExerciseCheckList) -> String {
+        report
+            .exercises
+            .iter()
+            .filter(|exercise| !exercise.result)
+            .map(|exercise| {
+                format!(
+                    "::error file={},line={},title={}::{}",
+                    escape_property(&exercise.path),
+                    failure_line(exercise),
+                    escape_property(&format!("{}: {}", exercise.name, exercise.outcome)),
+                    escape_data(&failure_message(exercise))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// The most useful one-line explanation of why an exercise failed:
+// the first compiler error, the first panic message, or the outcome itself
+fn failure_message(exercise: &ExerciseResult) -> String {
+    if let Some(error) = exercise
+        .stderr
+        .lines()
+        .find(|line| line.starts_with("error"))
+    {
+        return error.to_string();
+    }
+    let mut lines = exercise.stdout.lines().chain(exercise.stderr.lines());
+    while let Some(line) = lines.next() {
+        if line.contains("panicked at") {
+            return match lines.next() {
+                Some(message) if !message.trim().is_empty() => message.trim().to_string(),
+                _ => line.trim().to_string(),
+            };
+        }
+    }
+    exercise.outcome.to_string()
+}
+
+// The line in the exercise file that the compiler or a panic points at, or 1
+fn failure_line(exercise: &ExerciseResult) -> usize {
+    let re = Regex::new(r"(?:-->|panicked at) ([^\s:]+):(\d+):\d+").unwrap();
+    let path = exercise.path.trim_start_matches("./");
+    let line = re
+        .captures_iter(&exercise.stderr)
+        .chain(re.captures_iter(&exercise.stdout))
+        .find(|caps| caps[1].trim_start_matches("./").ends_with(path))
+        .and_then(|caps| caps[2].parse().ok());
+    line.unwrap_or(1)
+}
+
+// Escape text for XML, and for the HTML of the class report
+pub fn xml_escape(text: &str) -> String {
+    text.chars()
+        .filter(|&c| matches!(c, '\t' | '\n' | '\r') || c >= ' ')
+        .fold(String::new(), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                _ => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+// Escaping rules for the message of a GitHub workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// Escaping rules for the `key=value` properties of a GitHub workflow command
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
@@ -1,3 +1,5 @@
+// The tests predate clippy's needless_borrows_for_generic_args lint and keep their `.args(&[..])` style
+#![allow(clippy::needless_borrows_for_generic_args)]
 
 use assert_cmd::prelude::*;
 use std::process::Command;
@@ -0,0 +1,4 @@
+#[test]
+fn passing() {
+    asset!(true);
+}
@@ -0,0 +1,5 @@
+fn main() {
+    let greeting = String::from("hello");
+    let copy = greeting.clone();
+    println!("{greeting} {copy}");
+}
@@ -0,0 +1,17 @@
+mod vegetables;
+
+use vegetables::harvest;
+
+fn main() {
+    println!("{:?}", harvest());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn harvests_carrots() {
+        assert_eq!(harvest(), vec!["carrot"]);
+    }
+}
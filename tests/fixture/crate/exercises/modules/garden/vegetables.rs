@@ -0,0 +1,3 @@
+pub fn harvest() -> Vec<&'static str> {
+    vec!["carrot"]
+}
@@ -0,0 +1,5 @@
+// I AM NOT DONE
+
+pub fn pick() -> &'static str {
+    "apple"
+}
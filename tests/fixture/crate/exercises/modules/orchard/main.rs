@@ -0,0 +1,5 @@
+mod fruit;
+
+fn main() {
+    println!("Picked {}", fruit::pick());
+}
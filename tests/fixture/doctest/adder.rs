@@ -0,0 +1,10 @@
+/// Adds two numbers.
+///
+/// ```
+/// let sum = adder::add(2, 3);
+/// println!("2 + 3 = {sum}");
+/// assert_eq!(sum, 5);
+/// ```
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
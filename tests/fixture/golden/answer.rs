@@ -0,0 +1,3 @@
+fn main() {
+    println!("The answer is {}", 6 * 7);
+}
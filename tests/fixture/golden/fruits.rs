@@ -0,0 +1,8 @@
+use std::collections::HashSet;
+
+fn main() {
+    let fruits: HashSet<&str> = ["apple", "banana", "cherry"].into_iter().collect();
+    for fruit in fruits {
+        println!("{fruit}");
+    }
+}
@@ -0,0 +1,15 @@
+mod power;
+
+fn main() {
+    println!("{}", power::cube(3));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::power::cube;
+
+    #[test]
+    fn cubes_two() {
+        assert_eq!(cube(2), 8);
+    }
+}
@@ -0,0 +1,3 @@
+pub fn cube(n: i64) -> i64 {
+    n * n * n
+}
@@ -0,0 +1,4 @@
+#[test]
+fn cubes_negative() {
+    assert_eq!(power::cube(-2), -8);
+}
@@ -0,0 +1,9 @@
+#[test]
+fn squares_one() {
+    assert_eq!(square(1), 1);
+}
+
+#[test]
+fn squares_four() {
+    assert_eq!(square(4), 16);
+}
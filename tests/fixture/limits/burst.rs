@@ -0,0 +1,7 @@
+// Allocates far more than the limit in one go and exits right away,
+// faster than the memory use of the process could be polled
+
+fn main() {
+    let chunk = vec![1u8; 512 << 20];
+    println!("{}", chunk.iter().map(|&byte| byte as u64).sum::<u64>());
+}
@@ -0,0 +1,7 @@
+#[test]
+fn hog() {
+    let mut chunks = Vec::new();
+    loop {
+        chunks.push(vec![1u8; 1 << 20]);
+    }
+}
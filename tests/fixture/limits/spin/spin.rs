@@ -0,0 +1,11 @@
+// The test never finishes, so cargo test has to be stopped by the time limit
+
+fn main() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn spins_forever() {
+        loop {}
+    }
+}
@@ -0,0 +1,28 @@
+struct Rectangle {
+    width: i32,
+    height: i32,
+}
+
+impl Rectangle {
+    pub fn new(width: i32, height: i32) -> Self {
+        if width <= 0 || height <= 0 {
+            panic!("Rectangle width and height cannot be negative!");
+        }
+        Rectangle { width, height }
+    }
+
+    // The mutants don't have this method, so the tests can't be run against them
+    pub fn area(&self) -> i32 {
+        self.width * self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn area() {
+        assert_eq!(Rectangle::new(10, 20).area(), 200);
+    }
+}
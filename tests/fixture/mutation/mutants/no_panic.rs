@@ -0,0 +1,11 @@
+// Accepts negative sizes
+struct Rectangle {
+    width: i32,
+    height: i32,
+}
+
+impl Rectangle {
+    pub fn new(width: i32, height: i32) -> Self {
+        Rectangle { width, height }
+    }
+}
@@ -0,0 +1,17 @@
+// Mixes up width and height
+struct Rectangle {
+    width: i32,
+    height: i32,
+}
+
+impl Rectangle {
+    pub fn new(width: i32, height: i32) -> Self {
+        if width <= 0 || height <= 0 {
+            panic!("Rectangle width and height cannot be negative!");
+        }
+        Rectangle {
+            width: height,
+            height: width,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+#[test]
+fn fails() {
+    assert_eq!(2 + 2, 5);
+}
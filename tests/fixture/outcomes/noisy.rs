@@ -0,0 +1,5 @@
+fn main() {
+    for _ in 0..100 {
+        println!("{}", "x".repeat(99));
+    }
+}
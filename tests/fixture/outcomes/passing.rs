@@ -0,0 +1,4 @@
+#[test]
+fn passes() {
+    assert_eq!(2 + 2, 4);
+}
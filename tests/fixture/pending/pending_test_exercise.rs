@@ -0,0 +1,4 @@
+// I AM NOT DONE
+
+#[test]
+fn it_works() {}
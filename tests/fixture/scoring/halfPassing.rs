@@ -0,0 +1,9 @@
+#[test]
+fn passing() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+fn failing() {
+    assert_eq!(1 + 1, 3);
+}
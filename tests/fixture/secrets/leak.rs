@@ -0,0 +1,8 @@
+fn main() {
+    println!("compile time: {:?}", option_env!("RUSTLINGS_SIGNING_KEY"));
+    println!("run time: {:?}", std::env::var("RUSTLINGS_SIGNING_KEY").ok());
+    let parent = std::os::unix::process::parent_id();
+    let environ = std::fs::read(format!("/proc/{parent}/environ")).unwrap_or_default();
+    let leaked = String::from_utf8_lossy(&environ).contains("RUSTLINGS_SIGNING_KEY");
+    println!("parent environment: {leaked}");
+}
@@ -0,0 +1,5 @@
+// `async` is only a keyword since the 2018 edition
+fn main() {
+    let async = 2015;
+    println!("edition {}", async);
+}
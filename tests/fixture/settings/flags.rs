@@ -0,0 +1,13 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn sees_the_configured_flag() {
+        assert!(cfg!(course_build));
+        assert!(!cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn sees_the_compile_time_environment() {
+        assert_eq!(env!("COURSE"), "rustlings");
+    }
+}
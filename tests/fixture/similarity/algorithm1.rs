@@ -0,0 +1,17 @@
+// Sort the numbers in ascending order.
+
+fn sort(numbers: &mut Vec<i32>) {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut numbers = vec![3, 1, 2];
+        sort(&mut numbers);
+        assert_eq!(numbers, vec![1, 2, 3]);
+    }
+}
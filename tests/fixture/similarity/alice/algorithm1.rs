@@ -0,0 +1,29 @@
+// Sort the numbers in ascending order.
+
+fn sort(numbers: &mut Vec<i32>) {
+    let len = numbers.len();
+    for i in 0..len {
+        let mut swapped = false;
+        for j in 0..len - 1 - i {
+            if numbers[j] > numbers[j + 1] {
+                numbers.swap(j, j + 1);
+                swapped = true;
+            }
+        }
+        if !swapped {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut numbers = vec![3, 1, 2];
+        sort(&mut numbers);
+        assert_eq!(numbers, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,26 @@
+// Sort the numbers in ascending order.
+
+fn sort(numbers: &mut Vec<i32>) {
+    // bubble the largest element to the end on every pass
+    let n = numbers.len();
+    for pass in 0..n {
+        let mut changed = false;
+        for idx in 0..n - 1 - pass {
+            if numbers[idx] > numbers[idx + 1] { numbers.swap(idx, idx + 1); changed = true; }
+        }
+        /* nothing moved, so we are done */
+        if !changed { break; }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut numbers = vec![3, 1, 2];
+        sort(&mut numbers);
+        assert_eq!(numbers, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,22 @@
+// Sort the numbers in ascending order.
+
+fn sort(numbers: &mut Vec<i32>) {
+    let mut sorted: Vec<i32> = Vec::with_capacity(numbers.len());
+    while let Some(value) = numbers.pop() {
+        let position = sorted.iter().position(|&x| x > value).unwrap_or(sorted.len());
+        sorted.insert(position, value);
+    }
+    *numbers = sorted;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts() {
+        let mut numbers = vec![3, 1, 2];
+        sort(&mut numbers);
+        assert_eq!(numbers, vec![1, 2, 3]);
+    }
+}
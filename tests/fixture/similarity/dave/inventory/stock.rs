@@ -0,0 +1,11 @@
+pub fn restock(levels: Vec<u32>, minimum: u32) -> Vec<u32> {
+    let mut restocked = Vec::new();
+    for level in levels {
+        if level < minimum {
+            restocked.push(minimum);
+        } else {
+            restocked.push(level);
+        }
+    }
+    restocked
+}
@@ -0,0 +1,12 @@
+// my own implementation
+pub fn restock(counts: Vec<u32>, floor: u32) -> Vec<u32> {
+    let mut result = Vec::new();
+    for count in counts {
+        if count < floor {
+            result.push(floor);
+        } else {
+            result.push(count);
+        }
+    }
+    result
+}
@@ -0,0 +1,8 @@
+// Split the inventory code into its own module and make the tests pass
+
+mod stock;
+
+fn main() {
+    let items = stock::restock(vec![3, 0, 7], 5);
+    println!("{items:?}");
+}
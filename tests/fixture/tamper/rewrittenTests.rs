@@ -0,0 +1,14 @@
+pub fn is_even(n: i32) -> bool {
+    n % 2 == 0
+}
+
+// The student is meant to write these tests themselves
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn four_is_even() {
+        assert!(is_even(4));
+    }
+}
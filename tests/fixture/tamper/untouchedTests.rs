@@ -0,0 +1,13 @@
+pub fn brace() -> char {
+    '{'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_a_brace() {
+        assert_eq!(brace(), '{');
+    }
+}
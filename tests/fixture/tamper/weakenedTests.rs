@@ -0,0 +1,17 @@
+// @protected answer
+const ANSWER: i32 = 42;
+// @end-protected
+
+pub fn answer() -> i32 {
+    ANSWER
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_the_answer() {
+        assert!(true);
+    }
+}
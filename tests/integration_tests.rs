@@ -1,8 +1,14 @@
+// The tests predate clippy's needless_borrows_for_generic_args lint and keep their `.args(&[..])` style
+#![allow(clippy::needless_borrows_for_generic_args)]
+
 use assert_cmd::prelude::*;
 use glob::glob;
 use predicates::boolean::PredicateBooleanExt;
 use std::fs::File;
 use std::io::Read;
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
 
 #[test]
 fn runs_without_arguments() {
@@ -39,6 +45,28 @@ fn verify_fails_if_some_fails() {
         .code(1);
 }
 
+#[test]
+fn verify_only_selected_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["verify", "--name", "comp*"])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("testSuccess").not());
+}
+
+#[test]
+fn verify_range_with_unknown_exercise_fails() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["verify", "--from", "nonexistent"])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("No exercise found for 'nonexistent'!"));
+}
+
 #[test]
 fn run_single_compile_success() {
     Command::cargo_bin("rustlings")
@@ -263,3 +291,1622 @@ fn run_rustlings_list_without_done() {
         .success()
         .stdout(predicates::str::contains("Done").not());
 }
+
+#[test]
+fn run_single_exercise_timed_out() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "infiniteLoop"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("timed out after 1 s"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn run_single_exercise_out_of_memory() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "memoryHog"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("ran out of memory (limit 64 MB)"));
+}
+
+#[cfg(unix)]
+#[test]
+fn run_single_exercise_over_the_memory_limit_at_once() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "burst"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("ran out of memory (limit 64 MB)"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn run_build_script_exercise_timed_out() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "spin"])
+        .current_dir("tests/fixture/limits")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("timed out after 5 s"));
+    // The test binary cargo started is killed along with cargo
+    // (a zombie is dead, it just hasn't been reaped)
+    let running = std::fs::read_dir("/proc")
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("stat")).ok())
+        .any(|stat| stat.contains("(spin-") && !stat.contains(") Z "));
+    assert!(!running, "the spin test binary is still running");
+}
+
+// Copy a fixture into a fresh directory, so that tests can inspect
+// the files rustlings leaves behind without touching the fixture
+fn fixture_copy(fixture: &str, name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustlings_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    copy_dir(std::path::Path::new(&format!("tests/fixture/{fixture}")), &dir);
+    dir
+}
+
+fn copy_dir(source: &std::path::Path, destination: &std::path::Path) {
+    for entry in std::fs::read_dir(source).unwrap() {
+        let path = entry.unwrap().path();
+        let target = destination.join(path.file_name().unwrap());
+        if path.ends_with(".rustlings-cache") {
+            continue; // left behind by `run` and `verify` tests of the fixture
+        }
+        if path.is_dir() {
+            std::fs::create_dir_all(&target).unwrap();
+            copy_dir(&path, &target);
+        } else {
+            std::fs::copy(&path, target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn run_caches_exercise_results() {
+    let dir = fixture_copy("success", "cache");
+    for _ in 0..2 {
+        Command::cargo_bin("rustlings")
+            .unwrap()
+            .args(["run", "compSuccess"])
+            .current_dir(&dir)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("编译成功"));
+    }
+    let entries = std::fs::read_dir(dir.join(".rustlings-cache")).unwrap().count();
+    assert_eq!(entries, 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_ignores_forged_cache_entries() {
+    let dir = fixture_copy("outcomes", "forged_cache");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "broken"])
+        .current_dir(&dir)
+        .assert()
+        .code(1);
+    // Rewrite the cached compile error into a pass, as a student could
+    for entry in std::fs::read_dir(dir.join(".rustlings-cache")).unwrap() {
+        let path = entry.unwrap().path();
+        let forged = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace(r#""compiled":false"#, r#""compiled":true"#)
+            .replace(r#""outcome":"compile_error""#, r#""outcome":"passed""#);
+        std::fs::write(&path, forged).unwrap();
+    }
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "broken"])
+        .current_dir(&dir)
+        .assert()
+        .success();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--name", "broken", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "compile_error""#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_leaves_no_cache_behind() {
+    let dir = fixture_copy("outcomes", "grading_cache");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--name", "compiles", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .success();
+    assert!(!dir.join(".rustlings-cache").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_without_cache_leaves_no_cache_behind() {
+    let dir = fixture_copy("success", "no_cache");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["--no-cache", "run", "compSuccess"])
+        .current_dir(&dir)
+        .assert()
+        .success();
+    assert!(!dir.join(".rustlings-cache").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn similarity_reports_renamed_copies() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["similarity", "alice", "bob", "carol", "--base", "."])
+        .current_dir("tests/fixture/similarity")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("algorithm1: alice <-> bob"))
+        .stdout(predicates::str::contains("carol").not())
+        .stdout(predicates::str::contains("1 similar pairs among 3 submissions"));
+}
+
+#[test]
+fn similarity_shows_matched_lines() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["similarity", "alice", "bob", "--show-matches"])
+        .current_dir("tests/fixture/similarity")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("bob/algorithm1.rs: lines 3-"))
+        .stdout(predicates::str::contains("numbers.swap(idx, idx + 1)"));
+}
+
+#[test]
+fn similarity_compares_every_file_of_directory_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["similarity", "dave", "erin", "--name", "inventory", "--base", "."])
+        .current_dir("tests/fixture/similarity")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("inventory: dave <-> erin"))
+        .stdout(predicates::str::contains("erin/inventory/stock.rs: lines 2-"))
+        .stdout(predicates::str::contains("main.rs").not())
+        .stdout(predicates::str::contains("1 similar pairs among 2 submissions"));
+}
+
+#[test]
+fn run_directory_exercise() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "orchard"])
+        .current_dir("tests/fixture/crate")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Picked apple"));
+}
+
+#[test]
+fn verify_finds_marker_in_submodule_file() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("verify")
+        .current_dir("tests/fixture/crate")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "comment in exercises/modules/orchard/fruit.rs:",
+        ));
+}
+
+#[test]
+fn lsp_roots_directory_exercises_at_main_rs() {
+    let dir = fixture_copy("crate", "lsp");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("lsp")
+        .current_dir(&dir)
+        .assert()
+        .success();
+    let project = std::fs::read_to_string(dir.join("rust-project.json")).unwrap();
+    assert!(project.contains("garden/main.rs"));
+    assert!(project.contains("orchard/main.rs"));
+    assert!(!project.contains("vegetables.rs"));
+    assert!(!project.contains("fruit.rs"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_passes_args_environment_and_stdin() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "echo"])
+        .current_dir("tests/fixture/settings")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#"args: ["--greeting", "hello world"]"#))
+        .stdout(predicates::str::contains("name: Ferris"))
+        .stdout(predicates::str::contains("lines: 2"));
+}
+
+#[test]
+fn run_compiles_with_exercise_edition() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "edition2015"])
+        .current_dir("tests/fixture/settings")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("edition 2015"));
+}
+
+#[test]
+fn run_compiles_with_rustc_flags_and_environment() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "flags"])
+        .current_dir("tests/fixture/settings")
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_checks_expected_output() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "answer"])
+        .current_dir("tests/fixture/golden")
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_shows_diff_of_wrong_output() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "counting"])
+        .current_dir("tests/fixture/golden")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("  2\n- 3\n+ 4\n"))
+        .stdout(predicates::str::contains("its output is not what was expected"));
+}
+
+#[test]
+fn run_compile_fail_exercise_with_expected_error() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "moved"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_compile_fail_exercise_with_different_error() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "borrowed"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "borrowed.rs should fail to compile with error E0382, but rustc failed with E0502",
+        ));
+}
+
+#[test]
+fn run_compile_fail_exercise_that_compiles() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "cloned"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "cloned.rs compiled, but rustc should reject it with error E0382",
+        ));
+}
+
+#[test]
+fn compile_fail_exercises_require_an_expected_error() {
+    let dir = fixture_copy("compile_fail", "no_expected_error");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(dir.join("info.toml"), info.replace("expected_error = \"E0382\"\n", "")).unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "moved"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "moved.rs is in compile_fail mode but sets no expected_error",
+        ));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "moved: expected_error is required in, and only applies to, compile_fail mode",
+        ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_doctest_exercise_success() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "adder"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("successes:").not());
+}
+
+#[test]
+fn run_doctest_exercise_success_with_output() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["--nocapture", "run", "adder"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("adder.rs - add (line 3)"));
+}
+
+#[test]
+fn run_doctest_exercise_with_rustc_only_flags() {
+    let dir = fixture_copy("doctest", "doctest_flags");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(
+        dir.join("info.toml"),
+        info.replacen("hint = \"\"", "hint = \"\"\nrustc_flags = [\"-O\", \"-g\"]", 1),
+    )
+    .unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["--nocapture", "run", "adder"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("adder.rs - add (line 3)"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_rejects_flags_rustdoc_lacks() {
+    let dir = fixture_copy("doctest", "doctest_bad_flags");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(
+        dir.join("info.toml"),
+        info.replacen("hint = \"\"", "hint = \"\"\nrustc_flags = [\"--crate-type\", \"lib\"]", 1),
+    )
+    .unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "adder: rustc flag `--crate-type` has no rustdoc equivalent",
+        ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_doctest_exercise_failure() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["run", "halver"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "test result: FAILED. 1 passed; 1 failed",
+        ));
+}
+
+#[test]
+fn submit_dry_run_prints_summary() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["submit", "--report", "new.json", "--course-id", "7", "--name", "ferris", "--dry-run"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""courseId": 7"#))
+        .stdout(predicates::str::contains(r#""name": "ferris""#))
+        .stdout(predicates::str::contains(r#""score": 2,"#))
+        .stdout(predicates::str::contains(r#""totalScore": 3,"#))
+        .stdout(predicates::str::contains(r#""weightedScore": 2.0"#))
+        .stdout(predicates::str::contains(r#""maxWeightedScore": 3.0"#))
+        .stdout(predicates::str::contains("signedReport").not());
+}
+
+#[test]
+fn submit_sends_the_signed_report_along() {
+    let report = std::env::temp_dir().join(format!("rustlings_submit_signed_{}.json", std::process::id()));
+    std::fs::copy("tests/fixture/reports/new.json", &report).unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "sign", report.to_str().unwrap()])
+        .env("RUSTLINGS_SIGNING_KEY", "course secret")
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["submit", "--report", report.to_str().unwrap()])
+        .args(["--course-id", "7", "--name", "ferris", "--dry-run"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""signedReport": {"#))
+        .stdout(predicates::str::contains(r#""signature": ""#));
+    std::fs::remove_file(&report).unwrap();
+}
+
+#[test]
+fn submit_requires_a_student_name() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["submit", "--report", "new.json", "--course-id", "7", "--dry-run"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("--name"));
+}
+
+// Serve one response per status on a local port, sending back the
+// token header and body of every request that was received
+fn mock_server(statuses: Vec<u16>) -> (String, mpsc::Receiver<(Option<String>, String)>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/submit", server.server_addr());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for status in statuses {
+            let mut request = server.recv().unwrap();
+            let token = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("token"))
+                .map(|header| header.value.to_string());
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            sender.send((token, body)).unwrap();
+            let response = tiny_http::Response::from_string("{}").with_status_code(status);
+            request.respond(response).unwrap();
+        }
+    });
+    (endpoint, receiver)
+}
+
+#[test]
+fn submit_posts_summary_and_retries_on_server_errors() {
+    let (endpoint, requests) = mock_server(vec![503, 200]);
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["submit", "--report", "new.json", "--course-id", "7", "--name", "ferris"])
+        .args(["--endpoint", &endpoint, "--token", "secret"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("retrying"));
+    let requests: Vec<_> = requests.iter().collect();
+    assert_eq!(requests.len(), 2);
+    let (token, body) = &requests[1];
+    assert_eq!(token.as_deref(), Some("secret"));
+    assert!(body.contains(r#""courseId": 7"#));
+}
+
+#[test]
+fn submit_does_not_retry_rejected_submissions() {
+    let (endpoint, requests) = mock_server(vec![401]);
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["submit", "--report", "new.json", "--course-id", "7", "--name", "ferris"])
+        .args(["--endpoint", &endpoint])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("status 401"));
+    assert_eq!(requests.iter().count(), 1);
+}
+
+#[test]
+fn report_diff_lists_changes_between_runs() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "diff", "old.json", "new.json"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Newly passing (1):"))
+        .stdout(predicates::str::contains("variables1"))
+        .stdout(predicates::str::contains("functions1 (test failure)"))
+        .stdout(predicates::str::contains("Score: 2.00 -> 2.00"))
+        .stdout(predicates::str::contains("intro1: 200 ms -> 900 ms"));
+}
+
+#[test]
+fn report_diff_reads_reports_from_before_outcomes() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args([
+            "report",
+            "diff",
+            "--fail-on-regression",
+            "legacy/check_result.json",
+            "reports/new.json",
+        ])
+        .current_dir("tests/fixture")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Newly passing (1):"))
+        .stdout(predicates::str::contains("variables1"))
+        .stdout(predicates::str::contains("Score: 1.00 -> 2.00"))
+        .stdout(predicates::str::contains("Timing regressions (0):"));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "diff", "reports/new.json", "legacy/check_result.json"])
+        .current_dir("tests/fixture")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("variables1 (unknown, report predates outcomes)"));
+}
+
+#[test]
+fn report_diff_fails_on_regression() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "diff", "--fail-on-regression", "old.json", "new.json"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn report_diff_of_identical_reports_has_no_regressions() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "diff", "--fail-on-regression", "new.json", "new.json"])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Timing regressions (0):"));
+}
+
+#[test]
+fn report_verify_accepts_signed_reports_and_rejects_edited_ones() {
+    let output = std::env::temp_dir().join(format!("rustlings_signed_{}.json", std::process::id()));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", output.to_str().unwrap()])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+    assert!(!std::fs::read_to_string(&output).unwrap().contains(r#""signature": ""#));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "sign", output.to_str().unwrap()])
+        .env("RUSTLINGS_SIGNING_KEY", "course secret")
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+    let report = std::fs::read_to_string(&output).unwrap();
+    assert!(report.contains(r#""signature": ""#));
+
+    let verify = |key: &str| {
+        Command::cargo_bin("rustlings")
+            .unwrap()
+            .args(["report", "verify", output.to_str().unwrap()])
+            .env("RUSTLINGS_SIGNING_KEY", key)
+            .current_dir("tests/fixture/success")
+            .assert()
+    };
+    verify("course secret")
+        .success()
+        .stdout(predicates::str::contains("signature is valid"));
+    verify("another secret")
+        .code(1)
+        .stdout(predicates::str::contains("does not match"));
+
+    let forged = report.replace(r#""total_succeeds": 2"#, r#""total_succeeds": 3"#);
+    assert_ne!(forged, report);
+    std::fs::write(&output, forged).unwrap();
+    verify("course secret")
+        .code(1)
+        .stdout(predicates::str::contains("does not match"));
+    std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn report_verify_rejects_unsigned_reports() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["report", "verify", "new.json"])
+        .env("RUSTLINGS_SIGNING_KEY", "course secret")
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("the report is not signed"));
+}
+
+#[test]
+fn class_report_aggregates_student_reports() {
+    let output = std::env::temp_dir().join(format!("rustlings_class_{}", std::process::id()));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["class-report", ".", "--output", output.to_str().unwrap()])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Aggregated 2 reports"));
+
+    let exercises = std::fs::read_to_string(output.join("exercises.csv")).unwrap();
+    assert!(exercises.contains("intro1,intro,2,2,1.000,0,0\n"));
+    assert!(exercises.contains("functions1,functions,2,1,0.500,0,1\n"));
+    let students = std::fs::read_to_string(output.join("students.csv")).unwrap();
+    assert!(students.contains("new,3,2,2.00,3.00\n"));
+    let html = std::fs::read_to_string(output.join("index.html")).unwrap();
+    assert!(html.contains("<h2>Hardest exercises</h2>"));
+    assert!(html.contains("test failure (1)"));
+    std::fs::remove_dir_all(&output).unwrap();
+}
+
+#[test]
+fn class_report_reads_reports_from_before_outcomes() {
+    let output = std::env::temp_dir().join(format!("rustlings_class_legacy_{}", std::process::id()));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["class-report", ".", "--output", output.to_str().unwrap()])
+        .current_dir("tests/fixture/legacy")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Skipping summary.json, not a grading report"))
+        .stdout(predicates::str::contains("Aggregated 1 reports"));
+    let exercises = std::fs::read_to_string(output.join("exercises.csv")).unwrap();
+    assert!(exercises.contains("intro1,,1,1,1.000"));
+    // Failures without an outcome count as attempts, but under no outcome column
+    assert!(exercises.starts_with("exercise,category,attempts,passed,pass_rate\n"));
+    assert!(exercises.contains("variables1,,1,0,0.000"));
+    std::fs::remove_dir_all(&output).unwrap();
+}
+
+#[test]
+fn class_report_csv_headers_name_each_column_once() {
+    let output = std::env::temp_dir().join(format!("rustlings_class_headers_{}", std::process::id()));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["class-report", ".", "--output", output.to_str().unwrap()])
+        .current_dir("tests/fixture/reports")
+        .assert()
+        .success();
+
+    let header = |file: &str| {
+        let csv = std::fs::read_to_string(output.join(file)).unwrap();
+        csv.lines().next().unwrap().to_string()
+    };
+    let exercises = header("exercises.csv");
+    assert_eq!(exercises, "exercise,category,attempts,passed,pass_rate,compile error,test failure");
+    assert_eq!(header("students.csv"), "student,exercises,passed,score,max_score");
+    let mut columns: Vec<&str> = exercises.split(',').collect();
+    columns.sort();
+    columns.dedup();
+    assert_eq!(columns.len(), exercises.split(',').count());
+    std::fs::remove_dir_all(&output).unwrap();
+}
+
+// Run git in `dir` with a fixed identity, returning its stdout
+fn git(dir: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=rustlings", "-c", "user.email=rustlings@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// A git repository in a temporary directory with the files of a fixture committed
+fn git_fixture(fixture: &str, name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustlings_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for entry in std::fs::read_dir(format!("tests/fixture/{fixture}")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            std::fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    git(&dir, &["init", "--quiet"]);
+    git(&dir, &["add", "."]);
+    git(&dir, &["commit", "--quiet", "-m", "solutions"]);
+    dir
+}
+
+#[test]
+fn cicvverify_grades_a_git_revision() {
+    let dir = git_fixture("success", "rev");
+    // Break an exercise after the commit; the committed revision should still pass
+    std::fs::write(dir.join("testSuccess.rs"), "#[test]\nfn fails() {\n    assert!(false);\n}\n").unwrap();
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--rev", "HEAD", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""total_succeeds": 2"#));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""total_succeeds": 1"#));
+    assert_eq!(git(&dir, &["worktree", "list"]).lines().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_marks_late_submissions() {
+    let dir = git_fixture("success", "deadlines");
+    std::fs::write(
+        dir.join("info.toml"),
+        r#"
+[deadlines]
+late_penalty = 0.5
+
+[deadlines.categories]
+testSuccess = "2999-01-01T00:00:00+08:00"
+
+[[exercises]]
+name = "compSuccess"
+path = "compSuccess.rs"
+mode = "compile"
+hint = ""
+deadline = "2000-01-01T00:00:00Z"
+
+[[exercises]]
+name = "testSuccess"
+path = "testSuccess.rs"
+mode = "test"
+hint = ""
+"#,
+    )
+    .unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("compSuccess迟交"))
+        .stdout(predicates::str::contains(r#""late": true"#))
+        .stdout(predicates::str::contains(r#""deadline": "2999-01-01T00:00:00+08:00""#))
+        .stdout(predicates::str::contains(r#""total_score": 1.5"#))
+        .stdout(predicates::str::contains(r#""total_late": 1"#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_marks_uncommitted_exercises_late() {
+    let dir = git_fixture("success", "deadlines_untracked");
+    std::fs::copy(dir.join("compSuccess.rs"), dir.join("compNew.rs")).unwrap();
+    std::fs::write(
+        dir.join("info.toml"),
+        r#"
+[[exercises]]
+name = "compNew"
+path = "compNew.rs"
+mode = "compile"
+hint = ""
+deadline = "2000-01-01T00:00:00Z"
+"#,
+    )
+    .unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""late": true"#))
+        .stdout(predicates::str::contains(r#""total_late": 1"#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_rejects_invalid_deadlines() {
+    let dir = git_fixture("success", "bad_deadline");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(
+        dir.join("info.toml"),
+        info.replace("mode = \"compile\"", "mode = \"compile\"\ndeadline = \"next friday\""),
+    )
+    .unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("invalid deadline `next friday`"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_rejects_both_expected_output_and_file() {
+    let dir = fixture_copy("golden", "both_expected_outputs");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(
+        dir.join("info.toml"),
+        info.replace(
+            "expected_output_file = \"expected/fruits.txt\"",
+            "expected_output_file = \"expected/fruits.txt\"\nexpected_output = \"apple\"",
+        ),
+    )
+    .unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "fruits: set either expected_output or expected_output_file, not both",
+        ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_removes_the_worktree_when_validation_fails() {
+    let dir = git_fixture("success", "bad_deadline_rev");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(
+        dir.join("info.toml"),
+        info.replace("mode = \"compile\"", "mode = \"compile\"\ndeadline = \"next friday\""),
+    )
+    .unwrap();
+    git(&dir, &["commit", "-qam", "Add an invalid deadline"]);
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--rev", "HEAD", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("invalid deadline `next friday`"));
+    assert_eq!(git(&dir, &["worktree", "list"]).lines().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_removes_the_worktree_when_info_toml_is_malformed() {
+    let dir = git_fixture("success", "malformed_rev");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(dir.join("info.toml"), info.replace("[[exercises]]", "[[exercises]")).unwrap();
+    git(&dir, &["commit", "-qam", "Break info.toml"]);
+    std::fs::write(dir.join("info.toml"), info).unwrap();
+    git(&dir, &["commit", "-qam", "Fix info.toml"]);
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--rev", "HEAD~1", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("Failed to parse info.toml in the workspace"));
+    assert_eq!(git(&dir, &["worktree", "list"]).lines().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_grades_a_workspace_from_outside_rustlings() {
+    let dir = git_fixture("success", "outside");
+    let outside = std::env::temp_dir();
+    assert!(!outside.join("info.toml").exists());
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-", "--workspace"])
+        .arg(&dir)
+        .current_dir(&outside)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""total_succeeds": 2"#));
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--rev", "HEAD", "--output", "-", "--workspace"])
+        .arg(&dir)
+        .current_dir(&outside)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""total_succeeds": 2"#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn malformed_info_toml_is_reported_without_panicking() {
+    let dir = fixture_copy("success", "malformed_info");
+    let info = std::fs::read_to_string(dir.join("info.toml")).unwrap();
+    std::fs::write(dir.join("info.toml"), info.replace("[[exercises]]", "[[exercises]")).unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("Failed to parse info.toml"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn init_generates_the_same_variant_for_the_same_seed() {
+    let dir = git_fixture("variants", "init");
+    let rustlings = |args: &[&str]| {
+        Command::cargo_bin("rustlings")
+            .unwrap()
+            .args(args)
+            .current_dir(&dir)
+            .assert()
+    };
+    rustlings(&["init", "--seed", "alice"])
+        .success()
+        .stdout(predicates::str::contains("Generated 1 exercises for seed alice"));
+    let variant = std::fs::read_to_string(dir.join("multiply.rs")).unwrap();
+    assert!(!variant.contains("{{"));
+    assert_eq!(std::fs::read_to_string(dir.join(".rustlings-seed")).unwrap(), "alice\n");
+
+    // Another seed needs --force, and the same seed gives the same variant again
+    rustlings(&["init", "--seed", "bob"])
+        .code(1)
+        .stdout(predicates::str::contains("use --force"));
+    rustlings(&["init", "--seed", "bob", "--force"]).success();
+    assert_ne!(std::fs::read_to_string(dir.join("multiply.rs")).unwrap(), variant);
+    rustlings(&["init", "--seed", "alice", "--force"]).success();
+    assert_eq!(std::fs::read_to_string(dir.join("multiply.rs")).unwrap(), variant);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_checks_tests_against_the_students_variant() {
+    let dir = git_fixture("variants", "variant_grading");
+    let rustlings = |args: &[&str]| {
+        Command::cargo_bin("rustlings")
+            .unwrap()
+            .args(args)
+            .current_dir(&dir)
+            .assert()
+    };
+    rustlings(&["init", "--seed", "alice"]).success();
+    rustlings(&["cicvverify", "--seed", "alice", "--output", "-"])
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "passed""#));
+
+    // A variant copied from another student passes its own tests,
+    // but they aren't the tests of this student's variant
+    rustlings(&["init", "--seed", "bob", "--force"]).success();
+    rustlings(&["cicvverify", "--seed", "alice", "--output", "-"])
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "tampered""#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 0"#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_requires_a_seed_for_templated_exercises() {
+    let dir = git_fixture("variants", "variant_without_seed");
+    let rustlings = |args: &[&str]| {
+        Command::cargo_bin("rustlings")
+            .unwrap()
+            .args(args)
+            .current_dir(&dir)
+            .assert()
+    };
+    // The seed the student's repository records isn't trusted
+    rustlings(&["init", "--seed", "bob"]).success();
+    rustlings(&["cicvverify", "--output", "-"])
+        .code(1)
+        .stdout(predicates::str::contains("pass their seed with --seed"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// Grade the fixture with `cicvverify`, returning the JSON report
+fn grading_report(fixture: &str, args: &[&str]) -> serde_json::Value {
+    let output = Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .args(args)
+        .current_dir(format!("tests/fixture/{fixture}"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn cicvverify_classifies_each_outcome() {
+    let report = grading_report("outcomes", &[]);
+    let outcomes: Vec<(&str, &str)> = report["exercises"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|exercise| {
+            (
+                exercise["name"].as_str().unwrap(),
+                exercise["outcome"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("compiles", "passed"),
+            ("broken", "compile_error"),
+            ("panics", "runtime_error"),
+            ("failing", "test_failure"),
+            ("passing", "passed"),
+            ("noisy", "passed"),
+        ]
+    );
+    assert_eq!(report["statistics"]["total_succeeds"], 3);
+    assert_eq!(report["statistics"]["total_failures"], 3);
+}
+
+#[test]
+fn cicvverify_truncates_long_output() {
+    let report = grading_report("outcomes", &["--name", "noisy"]);
+    let stdout = report["exercises"][0]["stdout"].as_str().unwrap();
+    assert!(stdout.starts_with(&"x".repeat(99)));
+    assert!(stdout.ends_with("\n... (5904 more bytes truncated)"));
+}
+
+#[test]
+fn cicvverify_lists_results_in_info_toml_order() {
+    let report = grading_report("outcomes", &["--jobs", "4"]);
+    let exercises = report["exercises"].as_array().unwrap();
+    let names: Vec<&str> = exercises.iter().map(|e| e["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["compiles", "broken", "panics", "failing", "passing", "noisy"]);
+    let positions: Vec<u64> = exercises.iter().map(|e| e["position"].as_u64().unwrap()).collect();
+    assert_eq!(positions, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn cicvverify_jobs_do_not_change_the_report() {
+    // Everything but the timings and the output, which carries thread ids and timings too
+    fn results(report: &serde_json::Value) -> Vec<serde_json::Value> {
+        report["exercises"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|exercise| {
+                serde_json::json!([
+                    exercise["name"],
+                    exercise["outcome"],
+                    exercise["position"],
+                    exercise["score"],
+                ])
+            })
+            .collect()
+    }
+    let mut sequential = grading_report("outcomes", &["--jobs", "1"]);
+    let mut parallel = grading_report("outcomes", &["--jobs", "4"]);
+    assert_eq!(results(&sequential), results(&parallel));
+    for report in [&mut sequential, &mut parallel] {
+        report["statistics"]["total_time"].take();
+    }
+    assert_eq!(sequential["statistics"], parallel["statistics"]);
+}
+
+#[test]
+fn cicvverify_removes_its_build_directories() {
+    let tmp = std::env::temp_dir().join(format!("rustlings_build_dirs_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--jobs", "4", "--output", "-"])
+        .current_dir("tests/fixture/outcomes")
+        .env("TMPDIR", &tmp)
+        .assert()
+        .success();
+    let left: Vec<_> = std::fs::read_dir(&tmp).unwrap().map(|entry| entry.unwrap().path()).collect();
+    assert!(left.is_empty(), "left behind: {left:?}");
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cicvverify_writes_junit_report() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--format", "junit", "--output", "-"])
+        .current_dir("tests/fixture/broken")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            r#"<testsuite name="rustlings" tests="2" failures="2""#,
+        ))
+        .stdout(predicates::str::contains(r#"<failure message="error: expected pattern"#));
+}
+
+#[test]
+fn cicvverify_writes_tap_report() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--format", "tap", "--output", "-"])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("1..2\nok 1 - compSuccess\nok 2 - testSuccess"));
+}
+
+#[test]
+fn cicvverify_writes_github_annotations() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--format", "github"])
+        .current_dir("tests/fixture/broken")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "::error file=compFailure.rs,line=3,title=compFailure%3A compile error::",
+        ));
+}
+
+#[test]
+fn cicvverify_creates_missing_output_directory() {
+    let output = std::env::temp_dir()
+        .join(format!("rustlings_cicv_{}", std::process::id()))
+        .join("nested")
+        .join("check_result.json");
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", output.to_str().unwrap()])
+        .current_dir("tests/fixture/success")
+        .assert()
+        .success();
+    let report = std::fs::read_to_string(&output).unwrap();
+    assert!(report.contains(r#""total_succeeds": 2"#));
+    std::fs::remove_dir_all(output.parent().unwrap().parent().unwrap()).unwrap();
+}
+
+#[test]
+fn cicvverify_gives_partial_credit_for_passing_tests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/scoring")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""tests": {
+        "passed": 1,
+        "total": 2
+      }"#))
+        .stdout(predicates::str::contains(r#""total_score": 3.0,
+    "max_score": 5.0"#));
+}
+
+#[test]
+fn cicvverify_counts_tests_and_mutants_from_the_harness_not_the_output() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/forged")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""tests": {
+        "passed": 1,
+        "total": 2
+      }"#))
+        .stdout(predicates::str::contains(r#""mutants": {
+        "passed": 0,
+        "total": 1
+      }"#))
+        .stdout(predicates::str::contains(r#""total_score": 2.0,"#));
+}
+
+#[test]
+fn cicvverify_gives_no_credit_for_tests_the_student_adds() {
+    let report = grading_report("forged", &["--fingerprints", "fingerprints.toml", "--name", "paddedTests"]);
+    let padded = &report["exercises"][0];
+    assert_eq!(padded["outcome"], "test_failure");
+    assert_eq!(padded["tests"], serde_json::json!({ "passed": 0, "total": 1 }));
+    assert_eq!(padded["score"], 0.0);
+}
+
+#[test]
+fn cicvverify_fails_exercises_with_modified_tests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--tamper", "fail", "--fingerprints", "fingerprints.toml", "--output", "-"])
+        .current_dir("tests/fixture/tamper")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "tampered""#))
+        .stdout(predicates::str::contains(r#""tampered": [
+        "mod tests"
+      ]"#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 2"#));
+}
+
+#[test]
+fn cicvverify_fails_exercises_with_added_test_modules() {
+    let report = grading_report(
+        "tamper",
+        &["--tamper", "fail", "--fingerprints", "fingerprints.toml", "--name", "paddedTests"],
+    );
+    let padded = &report["exercises"][0];
+    assert_eq!(padded["outcome"], "tampered");
+    assert_eq!(padded["tampered"], serde_json::json!(["mod padding"]));
+    assert_eq!(padded["score"], 0.0);
+}
+
+#[test]
+fn cicvverify_flags_exercises_with_modified_tests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--tamper", "flag", "--fingerprints", "fingerprints.toml", "--output", "-"])
+        .current_dir("tests/fixture/tamper")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "tampered""#).not())
+        .stdout(predicates::str::contains(r#""tampered": ["#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 3"#));
+}
+
+#[test]
+fn cicvverify_leaves_out_test_modules_students_rewrite() {
+    let report = grading_report(
+        "tamper",
+        &["--tamper", "fail", "--fingerprints", "fingerprints.toml", "--name", "rewrittenTests"],
+    );
+    let rewritten = &report["exercises"][0];
+    assert_eq!(rewritten["outcome"], "passed");
+    assert!(rewritten["tampered"].is_null());
+}
+
+#[test]
+fn cicvverify_only_reads_fingerprints_it_is_given() {
+    // The fixture's fingerprints.toml sits in the graded checkout, where a student could regenerate it
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--tamper", "fail", "--output", "-"])
+        .env_remove("RUSTLINGS_FINGERPRINTS")
+        .current_dir("tests/fixture/tamper")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("--tamper needs the course's fingerprints"));
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--tamper", "fail", "--output", "-"])
+        .env("RUSTLINGS_FINGERPRINTS", "fingerprints.toml")
+        .current_dir("tests/fixture/tamper")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "tampered""#));
+}
+
+#[test]
+fn cicvverify_fails_exercises_still_marked_not_done() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/pending")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""outcome": "not_done""#))
+        .stdout(predicates::str::contains(r#""not_done_marker": true"#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 1"#));
+}
+
+#[test]
+fn cicvverify_warns_about_not_done_marker() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--marker", "warn", "--output", "-"])
+        .current_dir("tests/fixture/pending")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("pending_exercise仍标记为 I AM NOT DONE，请删除该标记"))
+        .stdout(predicates::str::contains(r#""total_succeeds": 3"#));
+}
+
+#[test]
+fn cicvverify_can_ignore_not_done_marker() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--marker", "ignore", "--output", "-"])
+        .current_dir("tests/fixture/pending")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("请删除该标记").not())
+        .stdout(predicates::str::contains(r#""total_succeeds": 3"#));
+}
+
+#[test]
+fn cicvverify_grades_only_selected_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--name", "plain*", "--output", "-"])
+        .current_dir("tests/fixture/scoring")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("总的题目数: 1"))
+        .stdout(predicates::str::contains(r#""position": 2"#))
+        .stdout(predicates::str::contains(r#""total_exercations": 1"#));
+}
+
+#[test]
+fn cicvverify_regrades_exercises_failed_in_previous_report() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--failed-in", "previous_report.json", "--output", "-"])
+        .current_dir("tests/fixture/pending")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""name": "pending_test_exercise""#).not())
+        .stdout(predicates::str::contains(r#""total_exercations": 2"#));
+}
+
+#[test]
+fn cicvverify_records_student_and_run_metadata() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--student", "ferris", "--output", "-"])
+        .current_dir("tests/fixture/scoring")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""schema_version": 1"#))
+        .stdout(predicates::str::contains(r#""user_name": "ferris""#))
+        .stdout(predicates::str::contains(r#""rustlings_version": "5.5.1""#))
+        .stdout(predicates::str::contains(r#""rustc_version": "rustc "#))
+        .stdout(predicates::str::contains(r#""started_at": "#));
+}
+
+#[test]
+fn cicvverify_reads_student_from_environment() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .env("RUSTLINGS_STUDENT", "ferris")
+        .current_dir("tests/fixture/scoring")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""user_name": "ferris""#));
+}
+
+#[test]
+fn cicvverify_only_takes_the_email_from_git_along_with_the_name() {
+    let dir = git_fixture("success", "identity");
+    git(&dir, &["config", "user.name", "teacher"]);
+    git(&dir, &["config", "user.email", "teacher@example.com"]);
+    let grade = |args: &[&str]| {
+        let output = Command::cargo_bin("rustlings")
+            .unwrap()
+            .args(["cicvverify", "--output", "-"])
+            .args(args)
+            .env_remove("RUSTLINGS_STUDENT")
+            .env_remove("GITHUB_ACTOR")
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let report = grade(&[]);
+    assert!(report.contains(r#""user_name": "teacher""#));
+    assert!(report.contains(r#""user_email": "teacher@example.com""#));
+
+    let report = grade(&["--student", "ferris"]);
+    assert!(report.contains(r#""user_name": "ferris""#));
+    assert!(report.contains(r#""user_email": null"#));
+
+    let report = grade(&["--student", "ferris", "--student-email", "ferris@example.com"]);
+    assert!(report.contains(r#""user_email": "ferris@example.com""#));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cicvverify_hides_the_signing_key_from_exercises() {
+    // Exercises can read the environment of the grader through /proc, so grading
+    // refuses to run with the key set rather than trying to hide it
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .env("RUSTLINGS_SIGNING_KEY", "top-secret-key")
+        .current_dir("tests/fixture/secrets")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("is set, but grading runs student code"))
+        .stdout(predicates::str::contains("top-secret-key").not())
+        .stdout(predicates::str::contains("parent environment").not());
+
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .env_remove("RUSTLINGS_SIGNING_KEY")
+        .current_dir("tests/fixture/secrets")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            r#"compile time: None\nrun time: None\nparent environment: false"#,
+        ))
+        .stdout(predicates::str::contains(r#""signature": "#).not());
+}
+
+#[test]
+fn cicvverify_grades_another_workspace() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--workspace", "../success", "--output", "-"])
+        .current_dir("tests/fixture/failure")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""name": "testSuccess""#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 2"#));
+}
+
+#[test]
+fn cicvverify_reports_hidden_tests_separately() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--fingerprints", "fingerprints.toml", "--hidden-tests", "grader", "--output", "-"])
+        .current_dir("tests/fixture/hidden")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("square可见测试通过 1/1，隐藏测试通过 1/2"))
+        .stdout(predicates::str::contains(r#""outcome": "test_failure""#))
+        .stdout(predicates::str::contains(
+            "\"tests\": {\n        \"passed\": 1,\n        \"total\": 1\n      }",
+        ))
+        .stdout(predicates::str::contains(
+            "\"hidden_tests\": {\n        \"passed\": 1,\n        \"total\": 2\n      }",
+        ));
+}
+
+#[test]
+fn verify_leaves_hidden_tests_out() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .arg("verify")
+        .current_dir("tests/fixture/hidden")
+        .assert()
+        .success();
+}
+
+#[test]
+fn cicvverify_splices_hidden_tests_into_directory_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--fingerprints", "fingerprints.toml", "--hidden-tests", "grader", "--output", "-"])
+        .current_dir("tests/fixture/hidden")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("cube可见测试通过 1/1，隐藏测试通过 1/1"));
+}
+
+#[test]
+fn cicvverify_ignores_hidden_tests_in_the_checkout() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--fingerprints", "fingerprints.toml", "--output", "-"])
+        .current_dir("tests/fixture/hidden")
+        .env_remove("RUSTLINGS_HIDDEN_TESTS")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""total_succeeds": 2"#))
+        .stdout(predicates::str::contains("hidden_tests").not());
+}
+
+#[test]
+fn cicvverify_fails_only_the_exercise_missing_hidden_tests() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--fingerprints", "fingerprints.toml", "--output", "-"])
+        .current_dir("tests/fixture/hidden")
+        .env("RUSTLINGS_HIDDEN_TESTS", "grader_missing")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("square批改出错，需要人工检查"))
+        .stdout(predicates::str::contains(r#""outcome": "grader_error""#))
+        .stdout(predicates::str::contains("Failed to read hidden tests missing.rs"))
+        .stderr(predicates::str::contains("cube执行成功"));
+}
+
+#[test]
+fn cicvverify_grades_tests_against_mutants() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/mutation")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("thorough执行成功"))
+        .stderr(predicates::str::contains("trivial的测试没有发现全部错误实现"))
+        .stdout(predicates::str::contains("mutant mutants/no_panic.rs ... survived"))
+        .stdout(predicates::str::contains(r#""outcome": "mutant_survived""#))
+        .stderr(predicates::str::contains("extended批改出错，需要人工检查"))
+        .stdout(predicates::str::contains("mutant mutants/swapped.rs ... not graded"))
+        .stdout(predicates::str::contains(r#""outcome": "grader_error""#))
+        .stdout(predicates::str::contains(r#""score": 0.5"#))
+        .stdout(predicates::str::contains(r#""total_score": 1.5"#));
+}
+
+#[test]
+fn cicvverify_builds_mutants_like_the_real_implementation() {
+    // The student's tests fail whenever their build differs from the real one,
+    // so telling the mutants apart would catch all of them
+    let report = grading_report("mutation", &[]);
+    for name in ["disguised", "positional"] {
+        let exercise = report["exercises"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["name"] == name)
+            .unwrap();
+        assert_eq!(exercise["outcome"], "mutant_survived", "{name}");
+        assert_eq!(exercise["mutants"]["passed"], 0, "{name}");
+        assert_eq!(exercise["score"], 0.0, "{name}");
+    }
+}
+
+#[test]
+fn cicvverify_fails_exercises_with_wrong_output() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/golden")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("counting输出结果不正确"))
+        .stdout(predicates::str::contains(r#""outcome": "wrong_output""#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 3"#));
+}
+
+#[test]
+fn cicvverify_grades_compile_fail_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/compile_fail")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("moved执行成功"))
+        .stderr(predicates::str::contains("borrowed编译失败"))
+        .stderr(predicates::str::contains("cloned应当编译失败，却编译通过了"))
+        .stdout(predicates::str::contains(r#""outcome": "unexpectedly_compiled""#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 1"#));
+}
+
+#[test]
+fn cicvverify_grades_doctest_exercises() {
+    Command::cargo_bin("rustlings")
+        .unwrap()
+        .args(["cicvverify", "--output", "-"])
+        .current_dir("tests/fixture/doctest")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("adder执行成功"))
+        .stderr(predicates::str::contains("halver测试未通过"))
+        .stdout(predicates::str::contains(r#""outcome": "test_failure""#))
+        .stdout(predicates::str::contains(r#""total_succeeds": 1"#));
+}